@@ -4,6 +4,9 @@ pub enum AdjustDirection {
 }
 
 pub fn cycle_index(index: usize, len: usize, direction: AdjustDirection) -> usize {
+    if len == 0 {
+        return 0;
+    }
     match direction {
         AdjustDirection::Next => (index + 1) % len,
         AdjustDirection::Previous => (index + len - 1) % len,
@@ -12,11 +15,46 @@ pub fn cycle_index(index: usize, len: usize, direction: AdjustDirection) -> usiz
 
 pub fn step_index(index: usize, len: usize, direction: AdjustDirection) -> usize {
     match direction {
-        AdjustDirection::Next => (index + 1).min(len - 1),
+        AdjustDirection::Next => (index + 1).min(len.saturating_sub(1)),
         AdjustDirection::Previous => index.saturating_sub(1),
     }
 }
 
+pub fn page_index(index: usize, len: usize, page: usize, direction: AdjustDirection) -> usize {
+    match direction {
+        AdjustDirection::Next => index.saturating_add(page).min(len.saturating_sub(1)),
+        AdjustDirection::Previous => index.saturating_sub(page),
+    }
+}
+
+// First visible index of a `viewport`-tall window that keeps `selected` in view, moving `offset` as little as possible
+pub fn scroll_offset(offset: usize, selected: usize, viewport: usize, len: usize) -> usize {
+    let viewport = viewport.max(1);
+    let max_offset = len.saturating_sub(viewport);
+    if selected < offset {
+        selected
+    } else if selected >= offset + viewport {
+        selected + 1 - viewport
+    } else {
+        offset
+    }
+    .min(max_offset)
+}
+
+// Indices within `depth` steps of `index`, nearest first, alternating forwards and backwards
+pub fn neighbour_indices(index: usize, len: usize, depth: usize) -> Vec<usize> {
+    let mut neighbours = Vec::new();
+    for distance in 1..=depth {
+        if let Some(next) = index.checked_add(distance).filter(|&i| i < len) {
+            neighbours.push(next);
+        }
+        if let Some(previous) = index.checked_sub(distance) {
+            neighbours.push(previous);
+        }
+    }
+    neighbours
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,6 +88,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_empty_cycle() {
+        let dirs = [AdjustDirection::Next, AdjustDirection::Previous];
+
+        for dir in dirs {
+            assert_eq!(cycle_index(0, 0, dir), 0);
+        }
+    }
+
     #[test]
     fn test_forward_step() {
         let test_cases = [(0, 3, 1), (1, 3, 2), (2, 3, 2)];
@@ -78,4 +125,69 @@ mod tests {
             assert_eq!(step_index(0, 1, dir), 0);
         }
     }
+
+    #[test]
+    fn test_empty_step() {
+        let dirs = [AdjustDirection::Next, AdjustDirection::Previous];
+
+        for dir in dirs {
+            assert_eq!(step_index(0, 0, dir), 0);
+        }
+    }
+
+    #[test]
+    fn test_page_step() {
+        let test_cases = [(0, 10, 3), (3, 10, 6), (8, 10, 9), (9, 10, 9), (0, 0, 0)];
+
+        for test_case in test_cases {
+            let (index, len, expected) = test_case;
+            assert_eq!(page_index(index, len, 3, AdjustDirection::Next), expected);
+        }
+
+        let test_cases = [(0, 10, 0), (2, 10, 0), (6, 10, 3), (9, 10, 6)];
+
+        for test_case in test_cases {
+            let (index, len, expected) = test_case;
+            assert_eq!(
+                page_index(index, len, 3, AdjustDirection::Previous),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_scroll_offset() {
+        // (offset, selected, expected) with a viewport of 3 over 10 rows
+        let test_cases = [
+            (0, 0, 0),
+            (0, 2, 0),
+            (0, 3, 1),
+            (0, 9, 7),
+            (5, 6, 5),
+            (5, 4, 4),
+            (5, 0, 0),
+            (9, 9, 7),
+        ];
+
+        for test_case in test_cases {
+            let (offset, selected, expected) = test_case;
+            assert_eq!(scroll_offset(offset, selected, 3, 10), expected);
+        }
+    }
+
+    #[test]
+    fn test_scroll_offset_short_table() {
+        assert_eq!(scroll_offset(0, 1, 10, 3), 0);
+        assert_eq!(scroll_offset(2, 1, 10, 3), 0);
+        assert_eq!(scroll_offset(0, 0, 10, 0), 0);
+    }
+
+    #[test]
+    fn test_neighbour_indices() {
+        assert_eq!(neighbour_indices(5, 10, 2), vec![6, 4, 7, 3]);
+        assert_eq!(neighbour_indices(0, 10, 2), vec![1, 2]);
+        assert_eq!(neighbour_indices(9, 10, 2), vec![8, 7]);
+        assert_eq!(neighbour_indices(0, 1, 3), Vec::<usize>::new());
+        assert_eq!(neighbour_indices(4, 10, 0), Vec::<usize>::new());
+    }
 }
@@ -0,0 +1,53 @@
+use color_eyre::eyre;
+
+use super::App;
+use crate::AdjustDirection;
+use crate::command::{Command, CommandContext, LayoutCommand};
+use crate::layouts::SavedPane;
+
+impl App {
+    pub fn command_context(&self) -> CommandContext<'_> {
+        CommandContext {
+            headers: &self.col_headers,
+            basenames: &self.imagefile_basenames,
+            layouts: self.layouts.names(),
+        }
+    }
+
+    pub fn run_command(&mut self, line: &str) -> eyre::Result<()> {
+        match Command::parse(line, &self.command_context())? {
+            Command::Action(action) => self.perform(action)?,
+            Command::Goto(row) => self.move_selection(|_, _| row - 1),
+            Command::Tab(tab) => self.current_tab = tab,
+            Command::Image(image_id) => self
+                .pane_tree
+                .set_image(&self.current_pane_path, image_id)?,
+            Command::Sort(keys) => self.sort_by(keys),
+            Command::KeyColumn(column) => self.key_column = Some(column),
+            Command::Filter(expression) => self.set_filter(&expression)?,
+            Command::Search(query) => {
+                self.submit_search(&query)?;
+                self.jump_to_match(AdjustDirection::Next);
+            }
+            Command::Layout(LayoutCommand::Save(name)) => {
+                let layout = SavedPane::from_pane(&self.pane_tree, &self.imagefile_basenames);
+                self.layouts.insert(&name, layout)?;
+                self.show_message(format!("Saved layout \"{name}\""));
+            }
+            Command::Layout(LayoutCommand::Load(name)) => {
+                self.pane_tree = self.layouts.get(&name)?.to_pane(&self.imagefile_basenames);
+                self.current_pane_path = self
+                    .pane_tree
+                    .collect_leaf_paths()
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default();
+            }
+            Command::Layout(LayoutCommand::Delete(name)) => {
+                self.layouts.remove(&name)?;
+                self.show_message(format!("Deleted layout \"{name}\""));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::mpsc;
+use std::thread;
+
+use color_eyre::eyre;
+
+use super::App;
+use crate::AdjustDirection;
+use crate::coverage::Coverage;
+
+// Reading every image header takes a while, so it happens in the background the first time coverage is shown
+#[derive(Debug, Default)]
+pub enum CoverageScan {
+    #[default]
+    NotStarted,
+    Running(mpsc::Receiver<Coverage>),
+    Done,
+}
+
+impl App {
+    // Until then, coverage is based on which files exist
+    pub fn start_coverage_scan(&mut self) {
+        if !matches!(self.coverage_scan, CoverageScan::NotStarted) {
+            return;
+        }
+        let mut dirs = vec![std::path::PathBuf::new(); self.row_ids.len()];
+        for (dir, &id) in self.imagedir_paths.iter().zip(&self.row_ids) {
+            dirs[id] = dir.clone();
+        }
+        let basenames = self.imagefile_basenames.clone();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || sender.send(Coverage::scan(&dirs, &basenames)));
+        self.coverage_scan = CoverageScan::Running(receiver);
+    }
+
+    pub fn is_scanning_coverage(&self) -> bool {
+        matches!(self.coverage_scan, CoverageScan::Running(_))
+    }
+
+    // Takes the finished scan, if any; returns whether the coverage changed
+    pub fn poll_coverage_scan(&mut self) -> bool {
+        let CoverageScan::Running(receiver) = &self.coverage_scan else {
+            return false;
+        };
+        match receiver.try_recv() {
+            Ok(coverage) => {
+                self.coverage = coverage;
+                self.coverage_scan = CoverageScan::Done;
+                if let Some(order) = self.coverage_sort {
+                    self.sort_rows_by_coverage(order);
+                }
+                true
+            }
+            Err(mpsc::TryRecvError::Empty) => false,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.coverage_scan = CoverageScan::Done;
+                true
+            }
+        }
+    }
+
+    pub fn step_coverage_column(&mut self, direction: AdjustDirection) {
+        self.coverage_col_index = crate::step_index(
+            self.coverage_col_index,
+            self.imagefile_basenames.len(),
+            direction,
+        );
+    }
+
+    // Opens the focused cell's image in the focused pane
+    pub fn show_coverage_image(&mut self) -> eyre::Result<()> {
+        if self.coverage_col_index >= self.imagefile_basenames.len() {
+            eyre::bail!("There are no images to show");
+        }
+        self.pane_tree
+            .set_image(&self.current_pane_path, self.coverage_col_index)?;
+        self.current_tab = crate::Tab::Image;
+        Ok(())
+    }
+}
@@ -1,191 +1,303 @@
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use pixmux::AdjustDirection;
+use std::time::Duration;
+
+use color_eyre::eyre;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use ratatui::layout;
 
-use super::{App, Tab};
+use super::App;
+use super::prompt::{Prompt, PromptKind, PromptOutcome};
+use crate::AdjustDirection;
+use crate::keymap::{Action, KeyChord, Lookup};
+
+// How long to wait for input before checking on background image decodes
+const EVENT_POLL_TIMEOUT: Duration = Duration::from_millis(50);
+const COLUMN_WIDTH_STEP: i16 = 2;
+const PANE_RESIZE_STEP: i8 = 5;
 
 impl App {
-    pub fn handle_crossterm_events(&mut self) -> color_eyre::Result<()> {
+    pub fn handle_crossterm_events(&mut self) -> color_eyre::Result<bool> {
+        if !event::poll(EVENT_POLL_TIMEOUT)? {
+            return Ok(false);
+        }
         match event::read()? {
             Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
             Event::Mouse(_) => {}
             Event::Resize(_, _) => {}
             _ => {}
         }
-        Ok(())
+        Ok(true)
     }
 
-    pub fn on_key_event(&mut self, key: KeyEvent) {
-        match (key.modifiers, key.code) {
-            //
-            (_, KeyCode::Char('q')) => self.quit(),
-
-            //
-            (KeyModifiers::NONE, KeyCode::Tab) => {
-                self.current_tab = self.current_tab.cycle();
-            }
-
-            //
-            (KeyModifiers::NONE, KeyCode::Up) => {
-                self.current_datarow_index = pixmux::step_index(
-                    self.current_datarow_index,
-                    self.table_rows.len(),
-                    pixmux::AdjustDirection::Previous,
-                )
-            }
-            (KeyModifiers::NONE, KeyCode::Down) => {
-                self.current_datarow_index = pixmux::step_index(
-                    self.current_datarow_index,
-                    self.table_rows.len(),
-                    pixmux::AdjustDirection::Next,
-                )
-            }
-
-            //
-            (KeyModifiers::NONE, KeyCode::Char('h')) => match self.current_tab {
-                Tab::Image => {
-                    self.current_pane_path = self
-                        .pane_tree
-                        .navigate(
-                            &self.current_pane_path,
-                            layout::Direction::Horizontal,
-                            pixmux::AdjustDirection::Previous,
-                        )
-                        .unwrap();
-                }
-                Tab::Data => {}
-            },
-            (KeyModifiers::NONE, KeyCode::Char('j')) => match self.current_tab {
-                Tab::Image => {
-                    self.current_pane_path = self
-                        .pane_tree
-                        .navigate(
-                            &self.current_pane_path,
-                            layout::Direction::Vertical,
-                            pixmux::AdjustDirection::Next,
-                        )
-                        .unwrap();
-                }
-                Tab::Data => {}
-            },
-            (KeyModifiers::NONE, KeyCode::Char('k')) => match self.current_tab {
-                Tab::Image => {
-                    self.current_pane_path = self
-                        .pane_tree
-                        .navigate(
-                            &self.current_pane_path,
-                            layout::Direction::Vertical,
-                            pixmux::AdjustDirection::Previous,
-                        )
-                        .unwrap();
-                }
-                Tab::Data => {}
-            },
-            (KeyModifiers::NONE, KeyCode::Char('l')) => match self.current_tab {
-                Tab::Image => {
-                    self.current_pane_path = self
-                        .pane_tree
-                        .navigate(
-                            &self.current_pane_path,
-                            layout::Direction::Horizontal,
-                            pixmux::AdjustDirection::Next,
-                        )
-                        .unwrap();
-                }
-                Tab::Data => {}
-            },
-
-            //
-            (KeyModifiers::NONE, KeyCode::Char('r')) => match self.current_tab {
-                Tab::Image => {
-                    self.current_pane_path = self
-                        .pane_tree
-                        .split_leaf_at(&self.current_pane_path, layout::Direction::Horizontal)
-                        .unwrap();
-                }
-                Tab::Data => {}
-            },
-            (KeyModifiers::NONE, KeyCode::Char('b')) => match self.current_tab {
-                Tab::Image => {
-                    self.current_pane_path = self
-                        .pane_tree
-                        .split_leaf_at(&self.current_pane_path, layout::Direction::Vertical)
-                        .unwrap();
+    fn on_prompt_key_event(&mut self, key: KeyEvent) {
+        let Some(prompt) = &mut self.prompt else {
+            return;
+        };
+        let kind = prompt.kind;
+        match prompt.handle_key(key) {
+            PromptOutcome::Editing => {
+                if kind == PromptKind::Search {
+                    let input = prompt.input.clone();
+                    self.preview_search(&input);
                 }
-                Tab::Data => {}
-            },
-
-            //
-            (KeyModifiers::NONE, KeyCode::Char('x')) => match self.current_tab {
-                Tab::Image => {
-                    self.current_pane_path = self
-                        .pane_tree
-                        .remove_leaf_at(&self.current_pane_path)
-                        .unwrap();
-                }
-                Tab::Data => {}
-            },
-
-            //
-            (KeyModifiers::NONE, KeyCode::Char('a')) => match self.current_tab {
-                Tab::Image => {
-                    self.pane_tree
-                        .resize_leaf_at(&self.current_pane_path, layout::Direction::Horizontal, -5)
-                        .unwrap();
+            }
+            PromptOutcome::Cancelled => {
+                if kind == PromptKind::Search {
+                    self.cancel_search();
                 }
-                Tab::Data => {}
-            },
-            (KeyModifiers::NONE, KeyCode::Char('s')) => match self.current_tab {
-                Tab::Image => {
-                    self.pane_tree
-                        .resize_leaf_at(&self.current_pane_path, layout::Direction::Vertical, 5)
-                        .unwrap();
+                self.prompt = None;
+            }
+            PromptOutcome::Complete(direction) => {
+                if kind == PromptKind::Command
+                    && let Some(mut prompt) = self.prompt.take()
+                {
+                    let context = self.command_context();
+                    prompt.complete(direction, |line| crate::command::complete(line, &context));
+                    self.prompt = Some(prompt);
                 }
-                Tab::Data => {}
-            },
-            (KeyModifiers::NONE, KeyCode::Char('w')) => match self.current_tab {
-                Tab::Image => {
-                    self.pane_tree
-                        .resize_leaf_at(&self.current_pane_path, layout::Direction::Vertical, -5)
-                        .unwrap();
+            }
+            PromptOutcome::Submitted(input) => {
+                // Closed first, so that a command can open another prompt
+                let prompt = self.prompt.take();
+                let result = match kind {
+                    PromptKind::Filter => self.set_filter(&input),
+                    PromptKind::Search => self.submit_search(&input),
+                    PromptKind::Command => self.run_command(&input),
+                };
+                // Leave the prompt open so the input can be corrected
+                if let (Err(err), Some(mut prompt)) = (result, prompt) {
+                    prompt.error = Some(err.to_string());
+                    self.prompt = Some(prompt);
                 }
-                Tab::Data => {}
-            },
-            (KeyModifiers::NONE, KeyCode::Char('d')) => match self.current_tab {
-                Tab::Image => {
-                    self.pane_tree
-                        .resize_leaf_at(&self.current_pane_path, layout::Direction::Horizontal, 5)
-                        .unwrap();
+            }
+        }
+    }
+
+    // Scrolls an overlay such as the help; returns false for keys that don't scroll it
+    fn scroll_overlay(offset: &mut usize, page: usize, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => *offset = offset.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => *offset += 1,
+            KeyCode::PageUp => *offset = offset.saturating_sub(page),
+            KeyCode::PageDown => *offset += page,
+            _ => return false,
+        }
+        true
+    }
+
+    // Esc and the keys bound to help or quit close an overlay; other keys are ignored
+    fn closes_overlay(&mut self, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Esc {
+            self.pending_keys.clear();
+            return true;
+        }
+        matches!(self.lookup_key(key), Some(Action::Help | Action::Quit))
+    }
+
+    // Keys that close an overlay, for its footer
+    pub fn overlay_close_keys(&self) -> Vec<String> {
+        let mut keys = vec!["Esc".to_string()];
+        for (action, sequences) in self.keymap.active_bindings(self.current_tab) {
+            if matches!(action, Action::Help | Action::Quit) {
+                keys.extend(
+                    sequences
+                        .iter()
+                        .map(|sequence| crate::keymap::format_sequence(sequence)),
+                );
+            }
+        }
+        keys
+    }
+
+    // Follows multi-key bindings across calls, returning the action once one is complete
+    fn lookup_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let chord = KeyChord::from(key);
+        let mut keys = std::mem::take(&mut self.pending_keys);
+        keys.push(chord);
+        let mut lookup = self.keymap.lookup(self.current_tab, &keys);
+        // A key that doesn't continue the pending sequence starts a new one
+        if lookup == Lookup::Unbound && keys.len() > 1 {
+            keys = vec![chord];
+            lookup = self.keymap.lookup(self.current_tab, &keys);
+        }
+        match lookup {
+            Lookup::Action(action) => Some(action),
+            Lookup::Pending => {
+                self.pending_keys = keys;
+                None
+            }
+            Lookup::Unbound => None,
+        }
+    }
+
+    pub fn on_key_event(&mut self, key: KeyEvent) {
+        if self.prompt.is_some() {
+            self.on_prompt_key_event(key);
+            return;
+        }
+        if self.show_help {
+            if !Self::scroll_overlay(&mut self.help_offset, self.overlay_page_size, key) {
+                self.show_help = !self.closes_overlay(key);
+            }
+            return;
+        }
+        if self.show_load_report {
+            if !Self::scroll_overlay(&mut self.load_report_offset, self.overlay_page_size, key) {
+                self.show_load_report = !self.closes_overlay(key);
+            }
+            return;
+        }
+        if let Some(action) = self.lookup_key(key)
+            && let Err(err) = self.perform(action)
+        {
+            self.show_error(err);
+        }
+    }
+
+    pub fn perform(&mut self, action: Action) -> eyre::Result<()> {
+        match action {
+            Action::Quit => self.quit(),
+            Action::NextTab => self.current_tab = self.current_tab.cycle(),
+
+            Action::RowUp => self.move_selection(|position, len| {
+                crate::step_index(position, len, AdjustDirection::Previous)
+            }),
+            Action::RowDown => self.move_selection(|position, len| {
+                crate::step_index(position, len, AdjustDirection::Next)
+            }),
+            Action::PageUp => {
+                let page = self.table_page_size;
+                self.move_selection(|position, len| {
+                    crate::page_index(position, len, page, AdjustDirection::Previous)
+                })
+            }
+            Action::PageDown => {
+                let page = self.table_page_size;
+                self.move_selection(|position, len| {
+                    crate::page_index(position, len, page, AdjustDirection::Next)
+                })
+            }
+            Action::FirstRow => self.move_selection(|_, _| 0),
+            Action::LastRow => self.move_selection(|_, len| len - 1),
+
+            Action::Search => self.start_search(),
+            Action::NextMatch => self.jump_to_match(AdjustDirection::Next),
+            Action::PreviousMatch => self.jump_to_match(AdjustDirection::Previous),
+            Action::Filter => {
+                self.prompt = Some(Prompt::new(PromptKind::Filter, &self.filter_expression));
+            }
+            Action::CommandLine => self.prompt = Some(Prompt::new(PromptKind::Command, "")),
+            Action::Help => {
+                self.show_help = true;
+                self.help_offset = 0;
+            }
+            Action::LoadReport => {
+                if self.load_issues.is_empty() {
+                    eyre::bail!("No rows were skipped while loading");
                 }
-                Tab::Data => {}
-            },
+                self.show_load_report = true;
+                self.load_report_offset = 0;
+            }
+            Action::Reload => self.reload()?,
+
+            Action::ColumnLeft => self.step_column(AdjustDirection::Previous),
+            Action::ColumnRight => self.step_column(AdjustDirection::Next),
+            Action::SortByCoverage => self.sort_by_coverage(),
+            Action::ShowImage => self.show_coverage_image()?,
+            Action::NarrowColumn => self.resize_current_column(-COLUMN_WIDTH_STEP),
+            Action::WidenColumn => self.resize_current_column(COLUMN_WIDTH_STEP),
+            Action::FreezeColumns => {
+                // Freeze every column up to the cursor, or unfreeze if that is already the case
+                let frozen = self.current_col_index + 1;
+                self.frozen_col_count = if self.frozen_col_count == frozen {
+                    0
+                } else {
+                    frozen
+                };
+            }
+            Action::Sort => self.sort_by_current_column(false),
+            Action::AddSortKey => self.sort_by_current_column(true),
+            Action::ClearSort => self.clear_sort(),
 
-            //
-            (_, KeyCode::Char('ä')) => match self.current_tab {
-                Tab::Image => self
+            Action::PaneLeft => {
+                self.navigate_pane(layout::Direction::Horizontal, AdjustDirection::Previous)?
+            }
+            Action::PaneDown => {
+                self.navigate_pane(layout::Direction::Vertical, AdjustDirection::Next)?
+            }
+            Action::PaneUp => {
+                self.navigate_pane(layout::Direction::Vertical, AdjustDirection::Previous)?
+            }
+            Action::PaneRight => {
+                self.navigate_pane(layout::Direction::Horizontal, AdjustDirection::Next)?
+            }
+            Action::SplitRight => {
+                self.current_pane_path = self
                     .pane_tree
-                    .cycle_image(
-                        &self.current_pane_path,
-                        self.imagefile_basenames.len(),
-                        AdjustDirection::Next,
-                    )
-                    .unwrap(),
-                Tab::Data => {}
-            },
-            (_, KeyCode::Char('ö')) => match self.current_tab {
-                Tab::Image => self
+                    .split_leaf_at(&self.current_pane_path, layout::Direction::Horizontal)?;
+            }
+            Action::SplitDown => {
+                self.current_pane_path = self
                     .pane_tree
-                    .cycle_image(
-                        &self.current_pane_path,
-                        self.imagefile_basenames.len(),
-                        AdjustDirection::Previous,
-                    )
-                    .unwrap(),
-                Tab::Data => {}
-            },
-
-            //
-            _ => {}
+                    .split_leaf_at(&self.current_pane_path, layout::Direction::Vertical)?;
+            }
+            Action::ClosePane => {
+                self.current_pane_path = self.pane_tree.remove_leaf_at(&self.current_pane_path)?;
+            }
+            Action::ShrinkPaneWidth => {
+                self.resize_pane(layout::Direction::Horizontal, -PANE_RESIZE_STEP)?
+            }
+            Action::GrowPaneWidth => {
+                self.resize_pane(layout::Direction::Horizontal, PANE_RESIZE_STEP)?
+            }
+            Action::ShrinkPaneHeight => {
+                self.resize_pane(layout::Direction::Vertical, -PANE_RESIZE_STEP)?
+            }
+            Action::GrowPaneHeight => {
+                self.resize_pane(layout::Direction::Vertical, PANE_RESIZE_STEP)?
+            }
+            Action::NextImage => self.pane_tree.cycle_image(
+                &self.current_pane_path,
+                self.imagefile_basenames.len(),
+                AdjustDirection::Next,
+            )?,
+            Action::PreviousImage => self.pane_tree.cycle_image(
+                &self.current_pane_path,
+                self.imagefile_basenames.len(),
+                AdjustDirection::Previous,
+            )?,
+        }
+        Ok(())
+    }
+
+    // The coverage tab has a column per image instead of per CSV column
+    fn step_column(&mut self, direction: AdjustDirection) {
+        if self.current_tab == crate::Tab::Coverage {
+            self.step_coverage_column(direction);
+            return;
+        }
+        self.current_col_index =
+            crate::step_index(self.current_col_index, self.col_headers.len(), direction);
+    }
+
+    fn navigate_pane(
+        &mut self,
+        direction: layout::Direction,
+        adjust: AdjustDirection,
+    ) -> eyre::Result<()> {
+        self.current_pane_path =
+            self.pane_tree
+                .navigate(&self.current_pane_path, direction, adjust)?;
+        Ok(())
+    }
+
+    fn resize_pane(&mut self, direction: layout::Direction, delta: i8) -> eyre::Result<()> {
+        self.pane_tree
+            .resize_leaf_at(&self.current_pane_path, direction, delta)
+    }
+
+    fn resize_current_column(&mut self, delta: i16) {
+        if let Some(width) = self.column_widths.get_mut(self.current_col_index) {
+            *width = crate::columns::resize_width(*width, delta);
         }
     }
 }
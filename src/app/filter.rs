@@ -0,0 +1,46 @@
+use super::App;
+use crate::filter::Filter;
+
+impl App {
+    // An empty expression removes the filter
+    pub fn set_filter(&mut self, expression: &str) -> color_eyre::Result<()> {
+        if expression.trim().is_empty() {
+            self.filter = None;
+            self.filter_expression.clear();
+        } else {
+            self.filter = Some(Filter::parse(expression, &self.col_headers, &self.schema)?);
+            self.filter_expression = expression.trim().to_string();
+        }
+        self.refresh_visible_rows();
+        Ok(())
+    }
+
+    // Keeps the selection on a visible row, preferring the next one down
+    pub fn refresh_visible_rows(&mut self) {
+        self.visible_rows = match &self.filter {
+            Some(filter) => filter.apply(&self.table_rows),
+            None => (0..self.table_rows.len()).collect(),
+        };
+        let position = self.visible_position();
+        if let Some(&row) = self.visible_rows.get(position).or(self.visible_rows.last()) {
+            self.current_datarow_index = row;
+        }
+    }
+
+    // Position of the selected row among the visible rows
+    pub fn visible_position(&self) -> usize {
+        self.visible_rows
+            .binary_search(&self.current_datarow_index)
+            .unwrap_or_else(|position| position)
+    }
+
+    // `adjust` maps the current visible position and the visible row count to a new position
+    pub fn move_selection(&mut self, adjust: impl FnOnce(usize, usize) -> usize) {
+        let len = self.visible_rows.len();
+        if len == 0 {
+            return;
+        }
+        let position = adjust(self.visible_position(), len).min(len - 1);
+        self.current_datarow_index = self.visible_rows[position];
+    }
+}
@@ -1,49 +1,211 @@
-use std::path;
-
 use color_eyre::Result;
-use pixmux::{Pane, Tab};
 use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::{DefaultTerminal, Frame};
+use ratatui_image::picker;
 
-use crate::ui;
+use crate::keymap::KeyChord;
+use crate::layouts::LayoutStore;
+use crate::tui::TuiOptions;
+use crate::{Config, DataSource, ImageCache, Keymap, Pane, Tab, ui};
 
+mod command;
+mod coverage;
 mod events;
+mod filter;
+pub mod prompt;
+mod reload;
+mod search;
+mod sort;
+mod status;
+
+// Used when the terminal does not answer the font-size query
+const FALLBACK_FONT_SIZE: (u16, u16) = (8, 16);
 
-#[derive(Default)]
 pub struct App {
     running: bool,
+    source: Box<dyn DataSource>,
     pub col_headers: Vec<String>,
     pub table_rows: Vec<Vec<String>>,
+    pub schema: crate::Schema,
     pub imagedir_paths: Vec<std::path::PathBuf>,
     pub imagefile_basenames: Vec<String>,
+    // Which of `imagefile_basenames` each row has, indexed by row id
+    pub image_presence: Vec<Vec<bool>>,
+    // Image status of every row, indexed by row id
+    pub coverage: crate::coverage::Coverage,
+    pub coverage_scan: coverage::CoverageScan,
+    pub coverage_col_index: usize,
+    pub coverage_col_offset: usize,
+    pub coverage_sort: Option<crate::sort::SortOrder>,
     pub current_tab: Tab,
     pub current_datarow_index: usize,
-    pub pane_tree: pixmux::Pane,
+    pub table_offset: usize,
+    pub column_widths: Vec<u16>,
+    pub current_col_index: usize,
+    pub frozen_col_count: usize,
+    pub col_offset: usize,
+    pub sort_keys: Vec<crate::sort::SortKey>,
+    pub row_ids: Vec<usize>,
+    pub filter: Option<crate::filter::Filter>,
+    pub filter_expression: String,
+    pub visible_rows: Vec<usize>,
+    pub prompt: Option<prompt::Prompt>,
+    pub show_help: bool,
+    pub message: Option<status::StatusMessage>,
+    pub key_column: Option<usize>,
+    pub help_offset: usize,
+    // Rows skipped by `--lenient` loading
+    pub load_issues: Vec<crate::dataset::RowIssue>,
+    pub show_load_report: bool,
+    pub load_report_offset: usize,
+    pub search: Option<crate::search::Search>,
+    // Selected row and previous search to go back to if the search prompt is cancelled
+    pub search_restore: Option<(usize, Option<crate::search::Search>)>,
+    pub table_page_size: usize,
+    // Rows the help or load report overlay shows at once
+    pub overlay_page_size: usize,
+    pub keymap: Keymap,
+    // Keys typed so far of a multi-key binding such as `g g`
+    pub pending_keys: Vec<KeyChord>,
+    pub pane_tree: crate::Pane,
+    pub layouts: LayoutStore,
     pub current_pane_path: Vec<bool>,
+    pub picker: picker::Picker,
+    pub image_cache: ImageCache,
+    pub prefetch_depth: usize,
 }
 
 impl App {
-    pub fn new(csv_path: path::PathBuf) -> Result<Self> {
-        let (col_headers, table_rows, imagedir_paths) = pixmux::parse_csv(&csv_path)?;
-        let imagefile_basenames = pixmux::imagefile::collect_basenames(&imagedir_paths);
-        Ok(Self {
+    pub fn new(
+        mut source: Box<dyn DataSource>,
+        config: Config,
+        options: &TuiOptions,
+    ) -> Result<Self> {
+        let dataset = source.load()?;
+        let picker = Self::create_picker(options);
+        let image_cache = ImageCache::new(
+            picker.clone(),
+            options.cache_memory,
+            ImageCache::default_workers(),
+        );
+        let (layouts, layouts_error) = match LayoutStore::open_default() {
+            Ok(layouts) => (layouts, None),
+            Err(error) => (
+                LayoutStore::unreadable(LayoutStore::default_path()),
+                Some(error),
+            ),
+        };
+        let mut app = Self {
             running: false,
-            col_headers,
-            table_rows,
-            imagedir_paths,
-            imagefile_basenames,
+            source,
+            col_headers: vec![],
+            table_rows: vec![],
+            schema: crate::Schema::default(),
+            imagedir_paths: vec![],
+            imagefile_basenames: vec![],
+            image_presence: vec![],
+            coverage: Default::default(),
+            coverage_scan: Default::default(),
+            coverage_col_index: 0,
+            coverage_col_offset: 0,
+            coverage_sort: None,
             current_tab: Tab::default(),
             current_datarow_index: 0,
+            table_offset: 0,
+            column_widths: vec![],
+            current_col_index: 0,
+            frozen_col_count: 0,
+            col_offset: 0,
+            sort_keys: vec![],
+            visible_rows: vec![],
+            row_ids: vec![],
+            filter: None,
+            filter_expression: String::new(),
+            prompt: None,
+            show_help: false,
+            message: None,
+            key_column: None,
+            help_offset: 0,
+            show_load_report: false,
+            load_issues: vec![],
+            load_report_offset: 0,
+            search: None,
+            search_restore: None,
+            table_page_size: 1,
+            overlay_page_size: 1,
+            keymap: config.keymap.clone(),
+            pending_keys: vec![],
             pane_tree: Pane::default(),
+            layouts,
             current_pane_path: vec![],
-        })
+            picker,
+            image_cache,
+            prefetch_depth: options.prefetch_depth,
+        };
+        app.set_dataset(dataset.as_ref());
+        // The first column usually identifies the row
+        app.key_column = match &config.key_column {
+            Some(name) => app.col_headers.iter().position(|h| h == name),
+            None => (!app.col_headers.is_empty()).then_some(0),
+        };
+        if let (Some(name), None) = (&config.key_column, app.key_column) {
+            app.show_error(format!("Unknown key column \"{name}\""));
+        }
+        for name in config.column_types.keys() {
+            if !app.col_headers.contains(name) {
+                app.show_error(format!("Unknown column \"{name}\" to set the type of"));
+            }
+        }
+        if let Some(error) = layouts_error {
+            app.show_error(error);
+        }
+        Ok(app)
+    }
+
+    // How many of the images seen across all rows exist for the row at `row`
+    pub fn available_images(&self, row: usize) -> usize {
+        self.row_ids
+            .get(row)
+            .and_then(|&id| self.image_presence.get(id))
+            .map_or(0, |presence| {
+                presence.iter().filter(|&&present| present).count()
+            })
+    }
+
+    // Whether the row at `row` has image `image_id`, as found when the rows were loaded
+    pub fn has_image(&self, row: usize, image_id: usize) -> bool {
+        self.row_ids
+            .get(row)
+            .and_then(|&id| self.image_presence.get(id))
+            .and_then(|presence| presence.get(image_id))
+            .is_some_and(|&present| present)
+    }
+
+    fn create_picker(options: &TuiOptions) -> picker::Picker {
+        let mut picker = match options.font_size {
+            Some(font_size) => picker::Picker::from_fontsize(font_size),
+            None => picker::Picker::from_query_stdio()
+                .unwrap_or_else(|_| picker::Picker::from_fontsize(FALLBACK_FONT_SIZE)),
+        };
+        if let Some(protocol) = options.protocol {
+            picker.set_protocol_type(protocol);
+        }
+        picker
     }
 
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
         self.running = true;
+        let mut needs_redraw = true;
         while self.running {
-            terminal.draw(|frame| self.render(frame))?;
-            self.handle_crossterm_events()?;
+            if needs_redraw {
+                terminal.draw(|frame| self.render(frame))?;
+            }
+            let handled_event = self.handle_crossterm_events()?;
+            let decoded_images = self.image_cache.poll();
+            self.report_image_failures();
+            let scanned_coverage = self.poll_coverage_scan();
+            let expired_message = self.expire_message();
+            needs_redraw = handled_event || decoded_images || scanned_coverage || expired_message;
         }
         Ok(())
     }
@@ -55,18 +217,33 @@ impl App {
     fn render(&mut self, frame: &mut Frame) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
             .split(frame.area());
 
         ui::NavBarUI::render(frame, chunks[0], self);
 
         match self.current_tab {
-            pixmux::Tab::Data => {
-                frame.render_widget(ui::TableTabUI::create_widget(self), chunks[1]);
+            crate::Tab::Data => {
+                ui::TableTabUI::render(frame, chunks[1], self);
             }
-            pixmux::Tab::Image => {
+            crate::Tab::Image => {
                 ui::ImageTabUI::render(frame, chunks[1], self);
             }
+            crate::Tab::Coverage => {
+                ui::CoverageTabUI::render(frame, chunks[1], self);
+            }
+        }
+
+        ui::StatusLineUI::render(frame, chunks[2], self);
+
+        if self.show_help {
+            ui::HelpUI::render(frame, chunks[1], self);
+        } else if self.show_load_report {
+            ui::LoadReportUI::render(frame, chunks[1], self);
         }
     }
 }
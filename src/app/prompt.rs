@@ -0,0 +1,142 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::AdjustDirection;
+use crate::command::Completion;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    Filter,
+    Search,
+    Command,
+}
+
+impl PromptKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PromptKind::Filter => "filter: ",
+            PromptKind::Search => "/",
+            PromptKind::Command => ":",
+        }
+    }
+}
+
+pub enum PromptOutcome {
+    Editing,
+    Submitted(String),
+    Cancelled,
+    // Tab or Shift+Tab asked to complete the word before the cursor
+    Complete(AdjustDirection),
+}
+
+// Candidates offered for the word starting at char `start`, `index` being the one inserted
+pub struct PromptCompletion {
+    pub start: usize,
+    pub candidates: Vec<String>,
+    pub index: usize,
+}
+
+// A single-line text input shown at the bottom of the screen
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+    // Counted in chars, not bytes
+    pub cursor: usize,
+    pub error: Option<String>,
+    pub completion: Option<PromptCompletion>,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind, input: &str) -> Self {
+        Self {
+            kind,
+            input: input.to_string(),
+            cursor: input.chars().count(),
+            error: None,
+            completion: None,
+        }
+    }
+
+    fn byte_offset(&self, cursor: usize) -> usize {
+        self.input
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.input.len(), |(offset, _)| offset)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> PromptOutcome {
+        match key.code {
+            KeyCode::Enter => return PromptOutcome::Submitted(self.input.clone()),
+            KeyCode::Esc => return PromptOutcome::Cancelled,
+            KeyCode::Tab => return PromptOutcome::Complete(AdjustDirection::Next),
+            KeyCode::BackTab => return PromptOutcome::Complete(AdjustDirection::Previous),
+            KeyCode::Char(c) => {
+                let offset = self.byte_offset(self.cursor);
+                self.input.insert(offset, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let offset = self.byte_offset(self.cursor);
+                self.input.remove(offset);
+            }
+            KeyCode::Delete if self.cursor < self.input.chars().count() => {
+                let offset = self.byte_offset(self.cursor);
+                self.input.remove(offset);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.input.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.input.chars().count(),
+            _ => {}
+        }
+        self.error = None;
+        self.completion = None;
+        PromptOutcome::Editing
+    }
+
+    // Replaces the word before the cursor with a candidate from `complete`, cycling through them on repeated presses.
+    // A unique candidate is inserted with a trailing space, ready for the next word.
+    pub fn complete(
+        &mut self,
+        direction: AdjustDirection,
+        complete: impl FnOnce(&str) -> Completion,
+    ) {
+        match &mut self.completion {
+            Some(completion) => {
+                completion.index =
+                    crate::cycle_index(completion.index, completion.candidates.len(), direction);
+            }
+            None => {
+                let before_cursor = &self.input[..self.byte_offset(self.cursor)];
+                let Completion { start, candidates } = complete(before_cursor);
+                if candidates.is_empty() {
+                    return;
+                }
+                let index = match direction {
+                    AdjustDirection::Next => 0,
+                    AdjustDirection::Previous => candidates.len() - 1,
+                };
+                self.completion = Some(PromptCompletion {
+                    start: before_cursor[..start].chars().count(),
+                    candidates,
+                    index,
+                });
+            }
+        }
+        let Some(completion) = &self.completion else {
+            return;
+        };
+        let start = completion.start;
+        let unique = completion.candidates.len() == 1;
+        let mut candidate = completion.candidates[completion.index].clone();
+        if unique {
+            candidate.push(' ');
+        }
+        let range = self.byte_offset(start)..self.byte_offset(self.cursor);
+        self.input.replace_range(range, &candidate);
+        self.cursor = start + candidate.chars().count();
+        if unique {
+            self.completion = None;
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use color_eyre::eyre;
+
+use super::App;
+use crate::Dataset;
+use crate::layouts::SavedPane;
+
+impl App {
+    // Replaces the rows, in the order the dataset has them
+    pub fn set_dataset(&mut self, dataset: &dyn Dataset) {
+        let row_count = dataset.row_count();
+        self.col_headers = dataset.headers().to_vec();
+        self.schema = dataset.schema().clone();
+        self.table_rows = (0..row_count)
+            .map(|row| {
+                (0..self.col_headers.len())
+                    .map(|column| dataset.cell(row, column).to_string())
+                    .collect()
+            })
+            .collect();
+        self.imagedir_paths = (0..row_count)
+            .map(|row| dataset.image_dir(row).to_path_buf())
+            .collect();
+        self.imagefile_basenames = crate::imagefile::collect_basenames(&self.imagedir_paths);
+        self.image_presence = self
+            .imagedir_paths
+            .iter()
+            .map(|dir| crate::imagefile::presence(dir, &self.imagefile_basenames))
+            .collect();
+        self.coverage = crate::coverage::Coverage::from_presence(&self.image_presence);
+        self.coverage_scan = super::coverage::CoverageScan::NotStarted;
+        self.coverage_sort = None;
+        self.column_widths = crate::columns::auto_widths(
+            &self.col_headers,
+            &self.table_rows,
+            crate::columns::MAX_AUTO_WIDTH,
+        );
+        self.row_ids = (0..row_count).collect();
+        self.visible_rows = self.row_ids.clone();
+        self.load_issues = dataset.issues().to_vec();
+        self.show_load_report = !self.load_issues.is_empty();
+        self.load_report_offset = 0;
+    }
+
+    // Sort, filter and column settings are kept when the columns haven't changed
+    pub fn reload(&mut self) -> eyre::Result<()> {
+        if !self.source.can_reload() {
+            eyre::bail!("{} can't be reloaded", self.source.name());
+        }
+        let dataset = self.source.load()?;
+        let same_columns = dataset.headers() == self.col_headers.as_slice();
+        let selected_id = self.row_ids.get(self.current_datarow_index).copied();
+        let selected_row = self.table_rows.get(self.current_datarow_index).cloned();
+        let selected_key = self
+            .key_column
+            .zip(selected_row.as_ref())
+            .and_then(|(column, row)| row.get(column))
+            .cloned();
+        let key_name = self
+            .key_column
+            .and_then(|column| self.col_headers.get(column))
+            .cloned();
+        let column_widths = std::mem::take(&mut self.column_widths);
+        let layout = SavedPane::from_pane(&self.pane_tree, &self.imagefile_basenames);
+
+        self.set_dataset(dataset.as_ref());
+        // Images may have changed along with the file
+        self.image_cache.forget_mtimes();
+        self.key_column =
+            key_name.and_then(|name| self.col_headers.iter().position(|h| *h == name));
+        self.pane_tree = layout.to_pane(&self.imagefile_basenames);
+        self.coverage_col_index = self
+            .coverage_col_index
+            .min(self.imagefile_basenames.len().saturating_sub(1));
+        if same_columns {
+            self.column_widths = column_widths;
+            // The column types may have changed
+            let expression = std::mem::take(&mut self.filter_expression);
+            if let Err(error) = self.set_filter(&expression) {
+                self.filter = None;
+                self.show_error(error);
+            }
+        } else {
+            self.sort_keys.clear();
+            self.filter = None;
+            self.filter_expression.clear();
+            self.search = None;
+            self.current_col_index = 0;
+            self.frozen_col_count = 0;
+            self.col_offset = 0;
+        }
+        // The selected row is found again by its key, or by its contents without a key column;
+        // only a row that is gone leaves the selection at its old position
+        let found = match (self.key_column, selected_key) {
+            (Some(column), Some(key)) => self
+                .table_rows
+                .iter()
+                .position(|row| row.get(column) == Some(&key)),
+            _ => selected_row
+                .and_then(|selected| self.table_rows.iter().position(|row| *row == selected)),
+        };
+        self.current_datarow_index = found.unwrap_or_else(|| {
+            selected_id
+                .unwrap_or_default()
+                .min(self.row_ids.len().saturating_sub(1))
+        });
+        self.apply_sort();
+        self.show_message(format!(
+            "Reloaded {} rows from {}",
+            self.row_ids.len(),
+            self.source.name()
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use color_eyre::eyre;
+
+use super::App;
+use super::prompt::{Prompt, PromptKind};
+use crate::AdjustDirection;
+use crate::search::Search;
+
+impl App {
+    pub fn start_search(&mut self) {
+        self.search_restore = Some((self.current_datarow_index, self.search.take()));
+        self.prompt = Some(Prompt::new(PromptKind::Search, ""));
+    }
+
+    // Jumps to the first match at or after the row the search started from, as the query is typed
+    pub fn preview_search(&mut self, query: &str) {
+        if let Some((origin, _)) = self.search_restore {
+            self.current_datarow_index = origin;
+        }
+        self.search = Search::parse(query, &self.col_headers).ok();
+        let len = self.visible_rows.len();
+        if len > 0 {
+            let before_origin = (self.visible_position() + len - 1) % len;
+            self.jump_to_match_from(before_origin, AdjustDirection::Next);
+        }
+    }
+
+    pub fn submit_search(&mut self, query: &str) -> eyre::Result<()> {
+        if query.is_empty() {
+            self.search = None;
+        } else {
+            let search = Search::parse(query, &self.col_headers)?;
+            let found = self
+                .visible_rows
+                .iter()
+                .any(|&row| search.matches_row(&self.table_rows[row]));
+            if !found {
+                return Err(eyre::eyre!("No match for \"{query}\""));
+            }
+            self.search = Some(search);
+        }
+        self.search_restore = None;
+        Ok(())
+    }
+
+    pub fn cancel_search(&mut self) {
+        if let Some((origin, search)) = self.search_restore.take() {
+            self.current_datarow_index = origin;
+            self.search = search;
+        }
+    }
+
+    pub fn jump_to_match(&mut self, direction: AdjustDirection) {
+        self.jump_to_match_from(self.visible_position(), direction);
+    }
+
+    fn jump_to_match_from(&mut self, position: usize, direction: AdjustDirection) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        if let Some(found) = search.find(&self.table_rows, &self.visible_rows, position, direction)
+        {
+            self.current_datarow_index = self.visible_rows[found];
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::App;
+use crate::sort::{self, SortKey, SortOrder};
+
+impl App {
+    // With `add_key` the focused column becomes an extra tie-breaking key,
+    // otherwise it replaces the current sort. Sorting again by a key flips its order.
+    pub fn sort_by_current_column(&mut self, add_key: bool) {
+        let column = self.current_col_index;
+        let existing = self.sort_keys.iter().position(|key| key.column == column);
+        match existing {
+            Some(pos) if add_key || self.sort_keys.len() == 1 => {
+                self.sort_keys[pos].order = self.sort_keys[pos].order.reversed();
+            }
+            _ => {
+                if !add_key {
+                    self.sort_keys.clear();
+                }
+                self.sort_keys.push(SortKey {
+                    column,
+                    order: SortOrder::Ascending,
+                });
+            }
+        }
+        self.apply_sort();
+    }
+
+    pub fn sort_by(&mut self, keys: Vec<SortKey>) {
+        self.sort_keys = keys;
+        self.apply_sort();
+    }
+
+    pub fn clear_sort(&mut self) {
+        self.sort_keys.clear();
+        self.apply_sort();
+    }
+
+    fn permute_rows(&mut self, permutation: &[usize]) {
+        sort::apply_permutation(&mut self.table_rows, permutation);
+        sort::apply_permutation(&mut self.imagedir_paths, permutation);
+        sort::apply_permutation(&mut self.row_ids, permutation);
+    }
+
+    // Rows with the fewest readable images come first, then the order flips on each use
+    pub fn sort_by_coverage(&mut self) {
+        let order = match self.coverage_sort {
+            Some(SortOrder::Ascending) => SortOrder::Descending,
+            _ => SortOrder::Ascending,
+        };
+        self.start_coverage_scan();
+        self.sort_rows_by_coverage(order);
+    }
+
+    // Sorted again once the coverage scan finishes
+    pub fn sort_rows_by_coverage(&mut self, order: SortOrder) {
+        self.sort_keys.clear();
+        self.reorder_rows(|app| {
+            let coverage = &app.coverage;
+            let mut permutation: Vec<usize> = (0..app.row_ids.len()).collect();
+            permutation.sort_by(|&a, &b| {
+                let ordering = coverage
+                    .present(app.row_ids[a])
+                    .cmp(&coverage.present(app.row_ids[b]));
+                match order {
+                    SortOrder::Ascending => ordering,
+                    SortOrder::Descending => ordering.reverse(),
+                }
+            });
+            Some(permutation)
+        });
+        self.coverage_sort = Some(order);
+    }
+
+    pub fn apply_sort(&mut self) {
+        self.reorder_rows(|app| {
+            (!app.sort_keys.is_empty())
+                .then(|| sort::sorted_permutation(&app.table_rows, &app.sort_keys, &app.schema))
+        });
+    }
+
+    // Rows are reordered in place, together with their image directories, keeping the selected row
+    fn reorder_rows(&mut self, permutation: impl FnOnce(&mut Self) -> Option<Vec<usize>>) {
+        let selected_id = self.row_ids.get(self.current_datarow_index).copied();
+        self.coverage_sort = None;
+
+        // Start from the file order, so that ties always keep it
+        let mut original_order: Vec<usize> = (0..self.row_ids.len()).collect();
+        original_order.sort_by_key(|&index| self.row_ids[index]);
+        self.permute_rows(&original_order);
+        if let Some(permutation) = permutation(self) {
+            self.permute_rows(&permutation);
+        }
+
+        if let Some(position) =
+            selected_id.and_then(|id| self.row_ids.iter().position(|&x| x == id))
+        {
+            self.current_datarow_index = position;
+        }
+        self.refresh_visible_rows();
+    }
+}
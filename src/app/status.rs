@@ -0,0 +1,65 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use super::App;
+
+const MESSAGE_DURATION: Duration = Duration::from_secs(5);
+
+// A message shown in the status bar for a few seconds
+pub struct StatusMessage {
+    pub text: String,
+    pub is_error: bool,
+    shown_at: Instant,
+}
+
+impl App {
+    pub fn show_message(&mut self, text: impl Into<String>) {
+        self.message = Some(StatusMessage {
+            text: text.into(),
+            is_error: false,
+            shown_at: Instant::now(),
+        });
+    }
+
+    pub fn show_error(&mut self, error: impl Display) {
+        self.message = Some(StatusMessage {
+            text: error.to_string(),
+            is_error: true,
+            shown_at: Instant::now(),
+        });
+    }
+
+    // Returns whether a message was cleared, which needs a redraw
+    pub fn expire_message(&mut self) -> bool {
+        let expired = self
+            .message
+            .as_ref()
+            .is_some_and(|message| message.shown_at.elapsed() >= MESSAGE_DURATION);
+        if expired {
+            self.message = None;
+        }
+        expired
+    }
+
+    pub fn report_image_failures(&mut self) {
+        for (path, error) in self.image_cache.take_failures() {
+            self.show_error(format!("Could not load {}: {error}", path.display()));
+        }
+    }
+
+    // Basename of the image in the focused pane
+    pub fn focused_image(&self) -> Option<&str> {
+        if self.current_tab == crate::Tab::Coverage {
+            return self
+                .imagefile_basenames
+                .get(self.coverage_col_index)
+                .map(String::as_str);
+        }
+        match self.pane_tree.get_node_at(&self.current_pane_path) {
+            Ok(crate::Pane::Leaf { image_id }) => {
+                self.imagefile_basenames.get(*image_id).map(String::as_str)
+            }
+            _ => None,
+        }
+    }
+}
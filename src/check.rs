@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::path;
+
+use color_eyre::Result;
+use serde::Serialize;
+
+use crate::coverage::{Coverage, ImageStatus};
+use crate::dataset::{DataSource, RowIssue, RowProblem};
+
+// Every problem refers to the line its row starts on in the CSV file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Problem {
+    MissingDir {
+        line: u64,
+        dir: path::PathBuf,
+    },
+    EmptyDir {
+        line: u64,
+    },
+    WrongColumnCount {
+        line: u64,
+        expected: usize,
+        found: usize,
+    },
+    InvalidJson {
+        line: u64,
+        error: String,
+    },
+    // Images that other rows have but this one doesn't
+    MissingImages {
+        line: u64,
+        images: Vec<String>,
+    },
+    UnreadableImages {
+        line: u64,
+        images: Vec<String>,
+    },
+    // Same values and image directory as the row on `first_line`
+    DuplicateRow {
+        line: u64,
+        first_line: u64,
+    },
+}
+
+impl Problem {
+    pub fn line(&self) -> u64 {
+        match self {
+            Problem::MissingDir { line, .. }
+            | Problem::EmptyDir { line }
+            | Problem::WrongColumnCount { line, .. }
+            | Problem::InvalidJson { line, .. }
+            | Problem::MissingImages { line, .. }
+            | Problem::UnreadableImages { line, .. }
+            | Problem::DuplicateRow { line, .. } => *line,
+        }
+    }
+}
+
+impl From<RowIssue> for Problem {
+    fn from(issue: RowIssue) -> Self {
+        let line = issue.line;
+        match issue.problem {
+            RowProblem::MissingDir(dir) => Problem::MissingDir { line, dir },
+            RowProblem::EmptyDir => Problem::EmptyDir { line },
+            RowProblem::WrongColumnCount { expected, found } => Problem::WrongColumnCount {
+                line,
+                expected,
+                found,
+            },
+            RowProblem::InvalidJson(error) => Problem::InvalidJson { line, error },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CheckReport {
+    // Name of the source, e.g. its path
+    pub file: String,
+    // Rows in the file, including the ones that couldn't be loaded
+    pub rows: usize,
+    // Every image basename found in any row directory
+    pub images: Vec<String>,
+    pub problems: Vec<Problem>,
+}
+
+impl CheckReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+// Row problems end up in the report only if the source is lenient; otherwise the first one is an error
+pub fn check(source: &mut dyn DataSource) -> Result<CheckReport> {
+    let dataset = source.load()?;
+    let column_count = dataset.headers().len();
+    let rows: Vec<Vec<&str>> = (0..dataset.row_count())
+        .map(|row| {
+            (0..column_count)
+                .map(|column| dataset.cell(row, column))
+                .collect()
+        })
+        .collect();
+    let row_dirs: Vec<path::PathBuf> = (0..dataset.row_count())
+        .map(|row| dataset.image_dir(row).to_path_buf())
+        .collect();
+    let images = crate::imagefile::collect_basenames(&row_dirs);
+    let coverage = Coverage::scan(&row_dirs, &images);
+
+    let mut problems: Vec<Problem> = Vec::new();
+    let mut first_lines: HashMap<(&Vec<&str>, &path::PathBuf), u64> = HashMap::new();
+    for (index, (row, dir)) in rows.iter().zip(&row_dirs).enumerate() {
+        let line = dataset.line(index);
+        let with_status = |wanted: ImageStatus| -> Vec<String> {
+            images
+                .iter()
+                .enumerate()
+                .filter(|&(image, _)| coverage.status(index, image) == wanted)
+                .map(|(_, basename)| basename.clone())
+                .collect()
+        };
+        let missing = with_status(ImageStatus::Missing);
+        if !missing.is_empty() {
+            problems.push(Problem::MissingImages {
+                line,
+                images: missing,
+            });
+        }
+        let unreadable = with_status(ImageStatus::Unreadable);
+        if !unreadable.is_empty() {
+            problems.push(Problem::UnreadableImages {
+                line,
+                images: unreadable,
+            });
+        }
+        if let Some(&first_line) = first_lines.get(&(row, dir)) {
+            problems.push(Problem::DuplicateRow { line, first_line });
+        } else {
+            first_lines.insert((row, dir), line);
+        }
+    }
+    problems.extend(dataset.issues().iter().cloned().map(Problem::from));
+    problems.sort_by_key(Problem::line);
+
+    Ok(CheckReport {
+        file: source.name(),
+        rows: dataset.row_count() + dataset.issues().len(),
+        images,
+        problems,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::dataset::LoadOptions;
+    use crate::source::FileSource;
+
+    #[test]
+    fn test_check_reports_row_problems() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["r1", "r2", "r3"] {
+            fs::create_dir(root.path().join(dir)).unwrap();
+        }
+        let png = |path: path::PathBuf| image::RgbaImage::new(2, 2).save(path).unwrap();
+        png(root.path().join("r1/a.png"));
+        png(root.path().join("r1/b.png"));
+        png(root.path().join("r2/a.png"));
+        fs::write(root.path().join("r3/a.png"), b"not a png").unwrap();
+        png(root.path().join("r3/b.png"));
+        let csv = root.path().join("index.csv");
+        fs::write(
+            &csv,
+            "model,_\nunet,r1\nresnet,r2\nvit,r3\nunet,r1\nvit,nope\n",
+        )
+        .unwrap();
+
+        let options = LoadOptions {
+            lenient: true,
+            ..LoadOptions::default()
+        };
+
+        let report = check(&mut FileSource::new(&csv, options)).unwrap();
+
+        assert!(!report.is_ok());
+        assert_eq!(report.rows, 5);
+        assert_eq!(report.images, vec!["a.png", "b.png"]);
+        assert_eq!(
+            report.problems,
+            vec![
+                Problem::MissingImages {
+                    line: 3,
+                    images: vec!["b.png".to_string()]
+                },
+                Problem::UnreadableImages {
+                    line: 4,
+                    images: vec!["a.png".to_string()]
+                },
+                Problem::DuplicateRow {
+                    line: 5,
+                    first_line: 2
+                },
+                Problem::MissingDir {
+                    line: 6,
+                    dir: root.path().join("nope")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_json() {
+        let report = CheckReport {
+            file: "index.csv".into(),
+            rows: 1,
+            images: vec!["a.png".to_string()],
+            problems: vec![Problem::EmptyDir { line: 2 }],
+        };
+
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"file":"index.csv","rows":1,"images":["a.png"],"problems":[{"kind":"empty_dir","line":2}]}"#
+        );
+    }
+}
@@ -0,0 +1,146 @@
+pub const MAX_AUTO_WIDTH: u16 = 40;
+pub const MAX_WIDTH: u16 = 200;
+
+// Each column is drawn after a one-cell gap
+const SPACING: u16 = 1;
+
+fn text_width(text: &str) -> u16 {
+    text.chars().count().min(u16::MAX as usize) as u16
+}
+
+// Headers get one extra cell so a sort arrow fits after them
+pub fn auto_widths(headers: &[String], rows: &[Vec<String>], max_width: u16) -> Vec<u16> {
+    let mut widths: Vec<u16> = headers
+        .iter()
+        .map(|h| text_width(h).saturating_add(1))
+        .collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(text_width(cell));
+        }
+    }
+    widths
+        .into_iter()
+        .map(|w| w.clamp(1, max_width.max(1)))
+        .collect()
+}
+
+pub fn resize_width(width: u16, delta: i16) -> u16 {
+    width.saturating_add_signed(delta).clamp(1, MAX_WIDTH)
+}
+
+// Columns that fit in `available` cells: the first `frozen` ones, then those from `offset` onwards.
+// The column at `offset` is always included so the cursor can never scroll out of view.
+pub fn visible_columns(widths: &[u16], frozen: usize, offset: usize, available: u16) -> Vec<usize> {
+    let frozen = frozen.min(widths.len());
+    let first_scrolling = offset.max(frozen);
+    let mut visible = Vec::new();
+    let mut used: u16 = 0;
+    for index in (0..frozen).chain(first_scrolling..widths.len()) {
+        let needed = used.saturating_add(widths[index]).saturating_add(SPACING);
+        if needed > available && index != first_scrolling {
+            if index < frozen {
+                continue;
+            }
+            break;
+        }
+        used = needed;
+        visible.push(index);
+    }
+    visible
+}
+
+// First scrolling column such that `cursor` is visible, moving `offset` as little as possible
+pub fn scroll_columns(
+    widths: &[u16],
+    frozen: usize,
+    offset: usize,
+    cursor: usize,
+    available: u16,
+) -> usize {
+    let frozen = frozen.min(widths.len());
+    let mut offset = offset.max(frozen);
+    if cursor < frozen {
+        return offset;
+    }
+    if cursor < offset {
+        return cursor;
+    }
+    while offset < cursor && !visible_columns(widths, frozen, offset, available).contains(&cursor) {
+        offset += 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_auto_widths_from_header_and_content() {
+        let headers = strings(&["id", "a_long_header", ""]);
+        let rows = vec![strings(&["12345", "x", ""]), strings(&["1", "y", ""])];
+
+        assert_eq!(auto_widths(&headers, &rows, 40), vec![5, 14, 1]);
+    }
+
+    #[test]
+    fn test_auto_widths_are_capped() {
+        let headers = strings(&["h"]);
+        let rows = vec![strings(&[&"x".repeat(100)])];
+
+        assert_eq!(auto_widths(&headers, &rows, 40), vec![40]);
+    }
+
+    #[test]
+    fn test_resize_width_is_clamped() {
+        assert_eq!(resize_width(10, 2), 12);
+        assert_eq!(resize_width(10, -2), 8);
+        assert_eq!(resize_width(1, -2), 1);
+        assert_eq!(resize_width(MAX_WIDTH, 2), MAX_WIDTH);
+    }
+
+    #[test]
+    fn test_visible_columns_all_fit() {
+        assert_eq!(visible_columns(&[3, 3, 3], 0, 0, 12), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_visible_columns_scrolled() {
+        assert_eq!(visible_columns(&[3, 3, 3, 3], 0, 1, 8), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_visible_columns_keep_frozen() {
+        assert_eq!(visible_columns(&[3, 3, 3, 3], 1, 2, 8), vec![0, 2]);
+        assert_eq!(visible_columns(&[3, 3, 3, 3], 1, 0, 8), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_visible_columns_too_wide_column_still_shown() {
+        assert_eq!(visible_columns(&[50, 3], 0, 0, 10), vec![0]);
+    }
+
+    #[test]
+    fn test_scroll_columns_follows_cursor() {
+        let widths = [3, 3, 3, 3, 3];
+
+        assert_eq!(scroll_columns(&widths, 0, 0, 1, 8), 0);
+        assert_eq!(scroll_columns(&widths, 0, 0, 2, 8), 1);
+        assert_eq!(scroll_columns(&widths, 0, 0, 4, 8), 3);
+        assert_eq!(scroll_columns(&widths, 0, 3, 1, 8), 1);
+    }
+
+    #[test]
+    fn test_scroll_columns_with_frozen() {
+        let widths = [3, 3, 3, 3, 3];
+
+        assert_eq!(scroll_columns(&widths, 1, 0, 0, 8), 1);
+        assert_eq!(scroll_columns(&widths, 1, 1, 3, 8), 3);
+        assert_eq!(scroll_columns(&widths, 1, 3, 0, 8), 3);
+    }
+}
@@ -0,0 +1,505 @@
+use color_eyre::eyre;
+
+use crate::Tab;
+use crate::filter::quote_column;
+use crate::keymap::Action;
+use crate::sort::{SortKey, SortOrder};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum LayoutCommand {
+    Save(String),
+    Load(String),
+    Delete(String),
+}
+
+// A parsed `:` command line with column and image names already resolved to indices
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    Action(Action),
+    // 1-based, as shown in the row-number gutter
+    Goto(usize),
+    Tab(Tab),
+    Image(usize),
+    Sort(Vec<SortKey>),
+    KeyColumn(usize),
+    Filter(String),
+    Search(String),
+    Layout(LayoutCommand),
+}
+
+// Commands taking arguments; every action name is a command as well
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("goto", "goto ROW"),
+    ("tab", "tab data|image|coverage"),
+    ("split", "split right|down"),
+    ("image", "image BASENAME"),
+    ("sort", "sort COLUMN [asc|desc]..."),
+    ("key-column", "key-column COLUMN"),
+    ("filter", "filter [EXPRESSION]"),
+    ("search", "search [QUERY]"),
+    ("layout", "layout save|load|delete NAME"),
+];
+
+const TAB_NAMES: &[(Tab, &str)] = &[
+    (Tab::Data, "data"),
+    (Tab::Image, "image"),
+    (Tab::Coverage, "coverage"),
+];
+const SPLIT_NAMES: &[(Action, &str)] =
+    &[(Action::SplitRight, "right"), (Action::SplitDown, "down")];
+const ORDER_NAMES: &[(SortOrder, &str)] = &[
+    (SortOrder::Ascending, "asc"),
+    (SortOrder::Descending, "desc"),
+];
+const LAYOUT_SUBCOMMANDS: &[&str] = &["save", "load", "delete"];
+
+// What names can refer to, for resolving arguments and completing them
+pub struct CommandContext<'a> {
+    pub headers: &'a [String],
+    pub basenames: &'a [String],
+    pub layouts: Vec<&'a str>,
+}
+
+struct Word {
+    start: usize,
+    text: String,
+}
+
+// Splits on whitespace; single or double quotes group words containing spaces
+fn split_words(line: &str) -> eyre::Result<Vec<Word>> {
+    let mut words = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut text = String::new();
+        let mut quote = None;
+        while let Some(&(_, c)) = chars.peek() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => text.push(c),
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if c.is_whitespace() => break,
+                None => text.push(c),
+            }
+            chars.next();
+        }
+        if quote.is_some() {
+            return Err(eyre::eyre!("Unterminated quote"));
+        }
+        words.push(Word { start, text });
+    }
+    Ok(words)
+}
+
+fn quote_if_needed(text: &str) -> String {
+    if text.is_empty() || text.contains(char::is_whitespace) || text.contains(['"', '\'']) {
+        if text.contains('"') {
+            format!("'{text}'")
+        } else {
+            format!("\"{text}\"")
+        }
+    } else {
+        text.to_string()
+    }
+}
+
+fn lookup<T: Copy>(table: &[(T, &str)], name: &str, what: &str) -> eyre::Result<T> {
+    table
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(value, _)| *value)
+        .ok_or_else(|| {
+            let names: Vec<&str> = table.iter().map(|(_, n)| *n).collect();
+            eyre::eyre!("Unknown {what} \"{name}\", expected {}", names.join("|"))
+        })
+}
+
+impl Command {
+    pub fn parse(line: &str, context: &CommandContext) -> eyre::Result<Self> {
+        let line = line.trim();
+        // Filters and searches take everything after the command name verbatim, quotes included
+        let (name, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(name, rest)| (name, rest.trim_start()));
+        match (name, rest.is_empty()) {
+            ("", _) => Err(eyre::eyre!("Empty command")),
+            ("filter", false) => Ok(Command::Filter(rest.to_string())),
+            ("search", false) => Ok(Command::Search(rest.to_string())),
+            _ => Self::parse_args(name, &split_words(rest)?, context),
+        }
+    }
+
+    fn parse_args(name: &str, args: &[Word], context: &CommandContext) -> eyre::Result<Self> {
+        let single_arg = |usage: &str| match args {
+            [arg] => Ok(arg.text.as_str()),
+            _ => Err(eyre::eyre!("Usage: {usage}")),
+        };
+        match (name, args.is_empty()) {
+            ("goto", _) => {
+                let row = single_arg("goto ROW")?;
+                match row.parse::<usize>() {
+                    Ok(row) if row > 0 => Ok(Command::Goto(row)),
+                    _ => Err(eyre::eyre!("Invalid row \"{row}\"")),
+                }
+            }
+            ("tab", _) => Ok(Command::Tab(lookup(
+                TAB_NAMES,
+                single_arg("tab data|image|coverage")?,
+                "tab",
+            )?)),
+            ("split", _) => Ok(Command::Action(lookup(
+                SPLIT_NAMES,
+                single_arg("split right|down")?,
+                "direction",
+            )?)),
+            ("image", _) => {
+                let basename = single_arg("image BASENAME")?;
+                context
+                    .basenames
+                    .iter()
+                    .position(|b| b == basename)
+                    .map(Command::Image)
+                    .ok_or_else(|| eyre::eyre!("No image named \"{basename}\""))
+            }
+            ("sort", false) => Self::parse_sort(args, context),
+            ("key-column", _) => {
+                let name = single_arg("key-column COLUMN")?;
+                context
+                    .headers
+                    .iter()
+                    .position(|h| h == name)
+                    .map(Command::KeyColumn)
+                    .ok_or_else(|| eyre::eyre!("Unknown column \"{name}\""))
+            }
+            ("layout", _) => {
+                let usage = "layout save|load|delete NAME";
+                let [subcommand, layout] = args else {
+                    return Err(eyre::eyre!("Usage: {usage}"));
+                };
+                let layout = layout.text.clone();
+                match subcommand.text.as_str() {
+                    "save" => Ok(Command::Layout(LayoutCommand::Save(layout))),
+                    "load" => Ok(Command::Layout(LayoutCommand::Load(layout))),
+                    "delete" => Ok(Command::Layout(LayoutCommand::Delete(layout))),
+                    _ => Err(eyre::eyre!("Usage: {usage}")),
+                }
+            }
+            (name, true) => name
+                .parse::<Action>()
+                .map(Command::Action)
+                .map_err(|_| eyre::eyre!("Unknown command \"{name}\"")),
+            (name, false) if name.parse::<Action>().is_ok() => {
+                Err(eyre::eyre!("\"{name}\" takes no arguments"))
+            }
+            (name, false) => Err(eyre::eyre!("Unknown command \"{name}\"")),
+        }
+    }
+
+    // `sort model loss desc` sorts by model ascending, then by loss descending
+    fn parse_sort(args: &[Word], context: &CommandContext) -> eyre::Result<Self> {
+        let mut keys: Vec<SortKey> = Vec::new();
+        for arg in args {
+            if let Some(column) = context.headers.iter().position(|h| *h == arg.text) {
+                keys.push(SortKey {
+                    column,
+                    order: SortOrder::Ascending,
+                });
+                continue;
+            }
+            let order = ORDER_NAMES
+                .iter()
+                .find(|(_, name)| *name == arg.text)
+                .map(|(order, _)| *order)
+                .ok_or_else(|| eyre::eyre!("Unknown column \"{}\"", arg.text))?;
+            match keys.last_mut() {
+                Some(key) => key.order = order,
+                None => {
+                    return Err(eyre::eyre!(
+                        "Expected a column name before \"{}\"",
+                        arg.text
+                    ));
+                }
+            }
+        }
+        Ok(Command::Sort(keys))
+    }
+}
+
+// Candidates for the word that ends at the end of `line`, quoted where needed
+#[derive(Clone, PartialEq, Debug)]
+pub struct Completion {
+    // Byte offset in `line` where the completed word starts
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+// Column names in filters are quoted with backticks; searches can only be scoped to names without spaces or colons
+fn complete_column(command: &str, line: &str, context: &CommandContext) -> Completion {
+    let after = |is_separator: fn(char) -> bool| {
+        line.char_indices()
+            .rev()
+            .find(|&(_, c)| is_separator(c))
+            .map_or(0, |(i, c)| i + c.len_utf8())
+    };
+    let start = if command != "filter" {
+        after(char::is_whitespace)
+    } else if line.matches('`').count() % 2 == 1 {
+        // Inside a backtick-quoted name
+        line.rfind('`').unwrap_or(0)
+    } else {
+        after(|c| c.is_whitespace() || "()!&|=<>~".contains(c))
+    };
+    let current = &line[start..];
+
+    let mut candidates: Vec<String> = Vec::new();
+    for header in context.headers {
+        let candidate = match command {
+            "filter" => quote_column(header),
+            _ if header.contains(char::is_whitespace) || header.contains(':') => continue,
+            _ => header.clone(),
+        };
+        if (header.starts_with(current) || candidate.starts_with(current))
+            && !candidates.contains(&candidate)
+        {
+            candidates.push(candidate);
+        }
+    }
+    Completion { start, candidates }
+}
+
+pub fn complete(line: &str, context: &CommandContext) -> Completion {
+    let empty = Completion {
+        start: line.len(),
+        candidates: vec![],
+    };
+    if let Some((command @ ("filter" | "search"), _)) =
+        line.trim_start().split_once(char::is_whitespace)
+    {
+        return complete_column(command, line, context);
+    }
+    let Ok(mut words) = split_words(line) else {
+        return empty;
+    };
+    // A trailing space means a new, empty word is being typed
+    if line.is_empty() || line.ends_with(char::is_whitespace) {
+        words.push(Word {
+            start: line.len(),
+            text: String::new(),
+        });
+    }
+    let Some((current, previous)) = words.split_last() else {
+        return empty;
+    };
+
+    let columns = || context.headers.iter().map(String::as_str);
+    let options: Vec<&str> = match previous {
+        [] => COMMANDS
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(Action::all().map(|action| action.name()))
+            .collect(),
+        [command, args @ ..] => match (command.text.as_str(), args) {
+            ("tab", []) => TAB_NAMES.iter().map(|(_, n)| *n).collect(),
+            ("split", []) => SPLIT_NAMES.iter().map(|(_, n)| *n).collect(),
+            ("image", []) => context.basenames.iter().map(String::as_str).collect(),
+            ("sort" | "key-column", []) => columns().collect(),
+            ("sort", _) => ORDER_NAMES
+                .iter()
+                .map(|(_, n)| *n)
+                .chain(columns())
+                .collect(),
+            ("layout", []) => LAYOUT_SUBCOMMANDS.to_vec(),
+            ("layout", [subcommand]) if subcommand.text != "save" => context.layouts.clone(),
+            _ => vec![],
+        },
+    };
+
+    let mut candidates: Vec<String> = Vec::new();
+    for option in options {
+        let candidate = quote_if_needed(option);
+        if option.starts_with(current.text.as_str()) && !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    Completion {
+        start: current.start,
+        candidates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn parse(line: &str) -> eyre::Result<Command> {
+        let headers = strings(&["model", "loss", "run name"]);
+        let basenames = strings(&["img.png", "mask.png"]);
+        let context = CommandContext {
+            headers: &headers,
+            basenames: &basenames,
+            layouts: vec!["grid4"],
+        };
+        Command::parse(line, &context)
+    }
+
+    fn candidates(line: &str) -> Vec<String> {
+        let headers = strings(&["model", "loss", "run name"]);
+        let basenames = strings(&["img.png", "mask.png"]);
+        let context = CommandContext {
+            headers: &headers,
+            basenames: &basenames,
+            layouts: vec!["grid4", "pair"],
+        };
+        complete(line, &context).candidates
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse("goto 120").unwrap(), Command::Goto(120));
+        assert_eq!(
+            parse("split right").unwrap(),
+            Command::Action(Action::SplitRight)
+        );
+        assert_eq!(parse("image mask.png").unwrap(), Command::Image(1));
+        assert_eq!(parse(" tab  image ").unwrap(), Command::Tab(Tab::Image));
+        assert_eq!(
+            parse("key-column 'run name'").unwrap(),
+            Command::KeyColumn(2)
+        );
+        assert_eq!(
+            parse("layout save grid4").unwrap(),
+            Command::Layout(LayoutCommand::Save("grid4".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!(
+            parse("sort loss desc").unwrap(),
+            Command::Sort(vec![SortKey {
+                column: 1,
+                order: SortOrder::Descending
+            }])
+        );
+        assert_eq!(
+            parse("sort model 'run name' desc").unwrap(),
+            Command::Sort(vec![
+                SortKey {
+                    column: 0,
+                    order: SortOrder::Ascending
+                },
+                SortKey {
+                    column: 2,
+                    order: SortOrder::Descending
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_rest_of_line_is_kept_verbatim() {
+        assert_eq!(
+            parse("filter loss < 0.2 &&  model == \"unet\"").unwrap(),
+            Command::Filter("loss < 0.2 &&  model == \"unet\"".to_string())
+        );
+        assert_eq!(
+            parse("search ~^S-0").unwrap(),
+            Command::Search("~^S-0".to_string())
+        );
+        // A lone quote is part of the expression, not the start of a quoted word
+        assert_eq!(
+            parse("search don't").unwrap(),
+            Command::Search("don't".to_string())
+        );
+        assert_eq!(
+            parse("filter note =~ \"don't\" || model == 'it''s'").unwrap(),
+            Command::Filter("note =~ \"don't\" || model == 'it''s'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_action_names_are_commands() {
+        assert_eq!(
+            parse("next-image").unwrap(),
+            Command::Action(Action::NextImage)
+        );
+        assert_eq!(parse("sort").unwrap(), Command::Action(Action::Sort));
+        assert_eq!(parse("filter").unwrap(), Command::Action(Action::Filter));
+    }
+
+    #[test]
+    fn test_err_invalid_commands() {
+        let bad = [
+            "",
+            "fly",
+            "quit now",
+            "goto",
+            "goto 0",
+            "goto x",
+            "split left",
+            "image nope.png",
+            "sort desc",
+            "sort accuracy",
+            "key-column accuracy",
+            "layout save",
+            "layout rename a",
+            "image \"mask.png",
+        ];
+
+        for line in bad {
+            assert!(parse(line).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_complete_command_names() {
+        assert_eq!(candidates("go"), vec!["goto"]);
+        assert_eq!(candidates("next-i"), vec!["next-image"]);
+        assert!(candidates("").contains(&"layout".to_string()));
+        assert_eq!(candidates("so"), vec!["sort", "sort-by-coverage"]);
+        assert_eq!(candidates("goto "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_complete_arguments() {
+        assert_eq!(candidates("image m"), vec!["mask.png"]);
+        assert_eq!(candidates("sort "), vec!["model", "loss", "\"run name\""]);
+        assert_eq!(candidates("sort loss d"), vec!["desc"]);
+        assert_eq!(candidates("layout load "), vec!["grid4", "pair"]);
+        assert_eq!(candidates("layout save "), Vec::<String>::new());
+        assert_eq!(candidates("split "), vec!["right", "down"]);
+    }
+
+    #[test]
+    fn test_complete_filter_and_search_columns() {
+        assert_eq!(candidates("filter "), vec!["model", "loss", "`run name`"]);
+        assert_eq!(candidates("filter loss < 0.2 && (ru"), vec!["`run name`"]);
+        assert_eq!(candidates("filter `run n"), vec!["`run name`"]);
+        assert_eq!(candidates("filter !mo"), vec!["model"]);
+        assert_eq!(candidates("search "), vec!["model", "loss"]);
+        assert_eq!(candidates("search don't lo"), vec!["loss"]);
+        assert_eq!(candidates("search ru"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_completion_start() {
+        let headers = strings(&["model"]);
+        let context = CommandContext {
+            headers: &headers,
+            basenames: &[],
+            layouts: vec![],
+        };
+
+        assert_eq!(complete("sort mo", &context).start, 5);
+        assert_eq!(complete("sort ", &context).start, 5);
+        assert_eq!(complete("filter (mo", &context).start, 8);
+        assert_eq!(complete("filter a == `mo", &context).start, 12);
+    }
+}
@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{self, WrapErr};
+use serde::Deserialize;
+
+use crate::keymap::{KeyContext, KeyOverrides, Keymap};
+use crate::schema::TypeOverrides;
+
+// Layout of config.toml, e.g.
+//
+//     [keys.global]
+//     "ctrl+d" = "page-down"
+//
+//     [keys.image]
+//     "." = "next-image"
+//     "ä" = "none"
+//
+//     [status]
+//     key_column = "sample"
+//
+//     [types]
+//     epoch = "integer"
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    keys: KeysSection,
+    status: StatusSection,
+    types: TypeOverrides,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StatusSection {
+    key_column: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeysSection {
+    global: KeyOverrides,
+    data: KeyOverrides,
+    image: KeyOverrides,
+    coverage: KeyOverrides,
+}
+
+// The directory in an XDG base directory variable, or `fallback` under the home directory
+pub(crate) fn xdg_home(variable: &str, fallback: &str) -> Option<PathBuf> {
+    std::env::var_os(variable)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub keymap: Keymap,
+    // Column whose value the status bar shows for the selected row
+    pub key_column: Option<String>,
+    // Column types used instead of the inferred ones, for columns with these names
+    pub column_types: TypeOverrides,
+}
+
+impl Config {
+    pub fn from_toml(text: &str) -> eyre::Result<Self> {
+        let file: ConfigFile = toml::from_str(text)?;
+        let keymap = Keymap::with_overrides(&[
+            (KeyContext::Global, &file.keys.global),
+            (KeyContext::Data, &file.keys.data),
+            (KeyContext::Image, &file.keys.image),
+            (KeyContext::Coverage, &file.keys.coverage),
+        ])?;
+        Ok(Self {
+            keymap,
+            key_column: file.status.key_column,
+            column_types: file.types,
+        })
+    }
+
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Could not read config file {}", path.display()))?;
+        Self::from_toml(&text).wrap_err_with(|| format!("Invalid config file {}", path.display()))
+    }
+
+    // `$XDG_CONFIG_HOME/pixmux/config.toml`, falling back to `~/.config`
+    pub fn default_path() -> Option<PathBuf> {
+        Some(
+            xdg_home("XDG_CONFIG_HOME", ".config")?
+                .join("pixmux")
+                .join("config.toml"),
+        )
+    }
+
+    // A missing config file just means the defaults
+    pub fn load_default() -> eyre::Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tab;
+    use crate::keymap::{Action, Lookup, parse_sequence};
+    use crate::schema::ColumnType;
+
+    #[test]
+    fn test_empty_config_is_default() {
+        let config = Config::from_toml("").unwrap();
+
+        assert_eq!(
+            config
+                .keymap
+                .lookup(Tab::Data, &parse_sequence("q").unwrap()),
+            Lookup::Action(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn test_key_sections() {
+        let config = Config::from_toml(
+            r#"
+            [keys.global]
+            "ctrl+d" = "page-down"
+
+            [keys.image]
+            "." = "next-image"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config
+                .keymap
+                .lookup(Tab::Image, &parse_sequence("ctrl+d").unwrap()),
+            Lookup::Action(Action::PageDown)
+        );
+        assert_eq!(
+            config
+                .keymap
+                .lookup(Tab::Image, &parse_sequence(".").unwrap()),
+            Lookup::Action(Action::NextImage)
+        );
+        assert_eq!(
+            config
+                .keymap
+                .lookup(Tab::Data, &parse_sequence(".").unwrap()),
+            Lookup::Unbound
+        );
+    }
+
+    #[test]
+    fn test_status_section() {
+        let config = Config::from_toml("[status]\nkey_column = \"sample\"").unwrap();
+
+        assert_eq!(config.key_column.as_deref(), Some("sample"));
+        assert_eq!(Config::default().key_column, None);
+    }
+
+    #[test]
+    fn test_types_section() {
+        let config = Config::from_toml("[types]\nepoch = \"integer\"\nrun = \"string\"").unwrap();
+
+        assert_eq!(
+            config.column_types,
+            TypeOverrides::from([
+                ("epoch".to_string(), ColumnType::Integer),
+                ("run".to_string(), ColumnType::String),
+            ])
+        );
+        assert!(Config::from_toml("[types]\nepoch = \"number\"").is_err());
+    }
+
+    #[test]
+    fn test_err_invalid_config() {
+        assert!(Config::from_toml("[keys.table]").is_err());
+        assert!(Config::from_toml("[keys.global]\nq = \"exit\"").is_err());
+        assert!(Config::from_toml("keys = 1").is_err());
+    }
+
+    #[test]
+    fn test_err_missing_file() {
+        assert!(Config::load(Path::new("/nonexistent/pixmux.toml")).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use std::path;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImageStatus {
+    Present,
+    Missing,
+    // The file exists but its header can't be decoded
+    Unreadable,
+}
+
+impl ImageStatus {
+    // Reads only the image header, which is enough to catch truncated or mislabelled files
+    pub fn check(path: &path::Path) -> Self {
+        if !path.is_file() {
+            return ImageStatus::Missing;
+        }
+        let dimensions = image::ImageReader::open(path)
+            .and_then(|reader| reader.with_guessed_format())
+            .map_err(image::ImageError::from)
+            .and_then(|reader| reader.into_dimensions());
+        match dimensions {
+            Ok(_) => ImageStatus::Present,
+            Err(_) => ImageStatus::Unreadable,
+        }
+    }
+}
+
+// The status of every image basename in every row directory
+#[derive(Clone, Debug, Default)]
+pub struct Coverage {
+    pub cells: Vec<Vec<ImageStatus>>,
+}
+
+impl Coverage {
+    pub fn scan(dirs: &[path::PathBuf], basenames: &[String]) -> Self {
+        let cells = dirs
+            .iter()
+            .map(|dir| {
+                basenames
+                    .iter()
+                    .map(|basename| ImageStatus::check(&dir.join(basename)))
+                    .collect()
+            })
+            .collect();
+        Self { cells }
+    }
+
+    // Without reading any headers, so every existing file counts as readable
+    pub fn from_presence(presence: &[Vec<bool>]) -> Self {
+        let cells = presence
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&present| {
+                        if present {
+                            ImageStatus::Present
+                        } else {
+                            ImageStatus::Missing
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { cells }
+    }
+
+    pub fn status(&self, row: usize, image: usize) -> ImageStatus {
+        self.cells
+            .get(row)
+            .and_then(|statuses| statuses.get(image))
+            .copied()
+            .unwrap_or(ImageStatus::Missing)
+    }
+
+    // Number of readable images in `row`
+    pub fn present(&self, row: usize) -> usize {
+        self.cells.get(row).map_or(0, |statuses| {
+            statuses
+                .iter()
+                .filter(|&&status| status == ImageStatus::Present)
+                .count()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn write_png(path: &path::Path) {
+        image::RgbaImage::new(2, 2).save(path).unwrap();
+    }
+
+    #[test]
+    fn test_check_image_status() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let good = tmpdir.path().join("good.png");
+        let corrupt = tmpdir.path().join("corrupt.png");
+        write_png(&good);
+        fs::write(&corrupt, b"not a png").unwrap();
+
+        assert_eq!(ImageStatus::check(&good), ImageStatus::Present);
+        assert_eq!(ImageStatus::check(&corrupt), ImageStatus::Unreadable);
+        assert_eq!(
+            ImageStatus::check(&tmpdir.path().join("missing.png")),
+            ImageStatus::Missing
+        );
+        assert_eq!(ImageStatus::check(tmpdir.path()), ImageStatus::Missing);
+    }
+
+    #[test]
+    fn test_scan() {
+        let dir1 = tempfile::tempdir().unwrap();
+        let dir2 = tempfile::tempdir().unwrap();
+        write_png(&dir1.path().join("a.png"));
+        write_png(&dir1.path().join("b.png"));
+        fs::write(dir2.path().join("a.png"), b"").unwrap();
+        let basenames = vec!["a.png".to_string(), "b.png".to_string()];
+
+        let coverage = Coverage::scan(
+            &[dir1.path().to_path_buf(), dir2.path().to_path_buf()],
+            &basenames,
+        );
+
+        assert_eq!(coverage.present(0), 2);
+        assert_eq!(coverage.present(1), 0);
+        assert_eq!(coverage.status(1, 0), ImageStatus::Unreadable);
+        assert_eq!(coverage.status(1, 1), ImageStatus::Missing);
+        assert_eq!(coverage.status(5, 0), ImageStatus::Missing);
+    }
+
+    #[test]
+    fn test_from_presence() {
+        let coverage = Coverage::from_presence(&[vec![true, false], vec![false, false]]);
+
+        assert_eq!(coverage.present(0), 1);
+        assert_eq!(coverage.status(0, 1), ImageStatus::Missing);
+        assert_eq!(coverage.present(1), 0);
+    }
+}
@@ -0,0 +1,351 @@
+use std::cmp::Ordering;
+use std::{fmt, path};
+
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use color_eyre::eyre::{self, OptionExt};
+
+use crate::parse_csv::CsvData;
+use crate::schema::{INFERRED_TYPES, Schema, TypeOverrides};
+use crate::source::STDIN_PATH;
+
+// A cell read as the type of its column
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value<'a> {
+    Empty,
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Timestamp(DateTime<Utc>),
+    Text(&'a str),
+}
+
+impl<'a> Value<'a> {
+    // Reads the text as the first type it fits, regardless of its column
+    pub fn parse(text: &'a str) -> Self {
+        INFERRED_TYPES
+            .iter()
+            .map(|column_type| column_type.parse(text))
+            .find(|value| !matches!(value, Value::Text(_)))
+            .unwrap_or(Value::Text(text))
+    }
+
+    // Integers and floats compare with each other; other values only with their own kind
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Empty, Value::Empty) => Some(Ordering::Equal),
+            (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
+            (Value::Integer(a), Value::Float(b)) => Some((*a as f64).total_cmp(b)),
+            (Value::Float(a), Value::Integer(b)) => Some(a.total_cmp(&(*b as f64))),
+            (Value::Float(a), Value::Float(b)) => Some(a.total_cmp(b)),
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (Value::Timestamp(a), Value::Timestamp(b)) => Some(a.cmp(b)),
+            (Value::Text(a), Value::Text(b)) => Some(crate::sort::compare_natural(a, b)),
+            _ => None,
+        }
+    }
+}
+
+// A table whose every row has a directory of images
+pub trait Dataset {
+    fn headers(&self) -> &[String];
+
+    fn row_count(&self) -> usize;
+
+    fn schema(&self) -> &Schema;
+
+    // Text of a cell as the Data tab shows it; cells past the end of a row are empty
+    fn cell(&self, row: usize, column: usize) -> &str;
+
+    fn value(&self, row: usize, column: usize) -> Value<'_> {
+        self.schema().value(column, self.cell(row, column))
+    }
+
+    fn image_dir(&self, row: usize) -> &path::Path;
+
+    // Where the row starts in its file, for reports
+    fn line(&self, row: usize) -> u64 {
+        row as u64 + 1
+    }
+
+    // Rows that were skipped while loading
+    fn issues(&self) -> &[RowIssue] {
+        &[]
+    }
+}
+
+// Where a dataset comes from; loading again picks up changes made since
+pub trait DataSource {
+    // Shown in messages, e.g. the file path
+    fn name(&self) -> String;
+
+    fn load(&mut self) -> Result<Box<dyn Dataset>>;
+
+    // Sources that can only be read once can't be reloaded
+    fn can_reload(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RowProblem {
+    MissingDir(path::PathBuf),
+    EmptyDir,
+    WrongColumnCount { expected: usize, found: usize },
+    // A JSON Lines line that isn't a JSON object
+    InvalidJson(String),
+}
+
+// A row that can't be loaded, with the line it starts on in the file (its 1-based position for Parquet and SQLite)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowIssue {
+    pub line: u64,
+    pub problem: RowProblem,
+}
+
+impl fmt::Display for RowIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.problem {
+            RowProblem::MissingDir(dir) => write!(f, "not a directory: {}", dir.display()),
+            RowProblem::EmptyDir => write!(f, "empty image directory"),
+            RowProblem::WrongColumnCount { expected, found } => {
+                write!(f, "expected {expected} fields, found {found}")
+            }
+            RowProblem::InvalidJson(error) => write!(f, "invalid JSON: {error}"),
+        }
+    }
+}
+
+// What every file format needs besides its own options
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    // Header name or 1-based position of the column holding the image directories, `_` if not set
+    pub image_column: Option<String>,
+    // What image directories are relative to, instead of the file's directory (or the working directory for stdin)
+    pub root: Option<path::PathBuf>,
+    // Column types to use instead of the inferred ones
+    pub column_types: TypeOverrides,
+    // Skip rows with problems and report them as issues instead of failing on the first
+    pub lenient: bool,
+}
+
+impl LoadOptions {
+    fn image_column_index(&self, headers: &[String]) -> Result<usize> {
+        let Some(name) = self.image_column.as_deref() else {
+            return headers
+                .iter()
+                .position(|h| h == "_")
+                .ok_or_eyre("Missing \"_\" column");
+        };
+        headers
+            .iter()
+            .position(|h| h == name)
+            .or_else(|| {
+                name.parse::<usize>()
+                    .ok()
+                    .filter(|position| (1..=headers.len()).contains(position))
+                    .map(|position| position - 1)
+            })
+            .ok_or_else(|| eyre::eyre!("No image column \"{name}\""))
+    }
+
+    // The directory that image directories of rows in `filepath` are relative to
+    pub(crate) fn base_dir(&self, filepath: &path::Path) -> Result<path::PathBuf> {
+        match &self.root {
+            Some(root) => Ok(root.clone()),
+            None if filepath == path::Path::new(STDIN_PATH) => Ok(path::PathBuf::new()),
+            None => filepath
+                .parent()
+                .map(path::Path::to_path_buf)
+                .ok_or_eyre("Could not determine parent directory of the file"),
+        }
+    }
+}
+
+// Rows as a file format reads them, before they are checked
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Records {
+    pub headers: Vec<String>,
+    // Every record with the line it starts on
+    pub rows: Vec<(u64, Vec<String>)>,
+    // Lines that couldn't be read as a record at all
+    pub issues: Vec<RowIssue>,
+    // Column types the file declares; inferred from the cells when it doesn't
+    pub schema: Option<Schema>,
+}
+
+// Rows held in memory, for loaders that read everything up front; `Schema::infer` gives a schema for them
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub image_dirs: Vec<path::PathBuf>,
+    // The line each row starts on in its file
+    pub lines: Vec<u64>,
+    pub issues: Vec<RowIssue>,
+    pub schema: Schema,
+}
+
+impl Table {
+    // Picks out the image column and checks every row's directory; problems with the file as a whole are always errors
+    pub(crate) fn from_records(
+        records: Records,
+        base_dir: &path::Path,
+        options: &LoadOptions,
+    ) -> Result<Self> {
+        let Records {
+            headers,
+            rows: records,
+            issues,
+            schema,
+        } = records;
+        let image_index = options.image_column_index(&headers)?;
+
+        let mut table = Table {
+            issues,
+            ..Table::default()
+        };
+        for (line, record) in records {
+            let problem = if record.len() != headers.len() {
+                Some(RowProblem::WrongColumnCount {
+                    expected: headers.len(),
+                    found: record.len(),
+                })
+            } else if record[image_index].is_empty() && options.lenient {
+                // Otherwise an empty directory is the base directory itself
+                Some(RowProblem::EmptyDir)
+            } else {
+                let dir = base_dir.join(&record[image_index]);
+                if dir.is_dir() {
+                    table.image_dirs.push(dir);
+                    None
+                } else {
+                    Some(RowProblem::MissingDir(dir))
+                }
+            };
+            match problem {
+                Some(problem) => table.issues.push(RowIssue { line, problem }),
+                None => {
+                    table.lines.push(line);
+                    table.rows.push(
+                        record
+                            .into_iter()
+                            .enumerate()
+                            .filter_map(|(i, val)| (i != image_index).then_some(val))
+                            .collect(),
+                    );
+                }
+            }
+        }
+
+        table.headers = headers
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, h)| (i != image_index).then_some(h))
+            .collect();
+        if table.headers.is_empty() {
+            return Err(eyre::eyre!("Missing data columns"));
+        }
+        table.issues.sort_by_key(|issue| issue.line);
+        if let (false, Some(issue)) = (options.lenient, table.issues.first()) {
+            return Err(eyre::eyre!("{issue}"));
+        }
+        let schema = match schema {
+            Some(mut schema) => {
+                if image_index < schema.types.len() {
+                    schema.types.remove(image_index);
+                }
+                schema
+            }
+            None => Schema::infer(&table.rows, table.headers.len()),
+        };
+        table.schema = schema.with_overrides(&table.headers, &options.column_types);
+        Ok(table)
+    }
+}
+
+impl From<CsvData> for Table {
+    fn from((headers, rows, image_dirs): CsvData) -> Self {
+        let schema = Schema::infer(&rows, headers.len());
+        Table {
+            headers,
+            lines: (1..=rows.len() as u64).collect(),
+            rows,
+            image_dirs,
+            issues: vec![],
+            schema,
+        }
+    }
+}
+
+impl Dataset for Table {
+    fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn cell(&self, row: usize, column: usize) -> &str {
+        self.rows[row].get(column).map_or("", String::as_str)
+    }
+
+    fn image_dir(&self, row: usize) -> &path::Path {
+        &self.image_dirs[row]
+    }
+
+    fn line(&self, row: usize) -> u64 {
+        self.lines.get(row).copied().unwrap_or(row as u64 + 1)
+    }
+
+    fn issues(&self) -> &[RowIssue] {
+        &self.issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(Value::parse(""), Value::Empty);
+        assert_eq!(Value::parse(" 12 "), Value::Integer(12));
+        assert_eq!(Value::parse("-0.5"), Value::Float(-0.5));
+        assert_eq!(Value::parse("true"), Value::Bool(true));
+        assert_eq!(Value::parse("unet"), Value::Text("unet"));
+        assert!(matches!(Value::parse("2024-01-05"), Value::Timestamp(_)));
+    }
+
+    #[test]
+    fn test_compare_values() {
+        let compare = |a, b| Value::parse(a).compare(&Value::parse(b));
+        assert_eq!(compare("2", "10"), Some(Ordering::Less));
+        assert_eq!(compare("2.5", "2"), Some(Ordering::Greater));
+        assert_eq!(compare("2024-01-05", "2023-12-31"), Some(Ordering::Greater));
+        assert_eq!(compare("img2", "img10"), Some(Ordering::Less));
+        assert_eq!(compare("true", "1"), None);
+        assert_eq!(compare("", "1"), None);
+    }
+
+    #[test]
+    fn test_table_dataset() {
+        let table = Table::from((
+            vec!["model".to_string(), "loss".to_string()],
+            vec![vec!["unet".to_string(), "0.15".to_string()], vec![]],
+            vec!["r1".into(), "r2".into()],
+        ));
+
+        assert_eq!(table.row_count(), 2);
+        assert_eq!(table.value(0, 1), Value::Float(0.15));
+        assert_eq!(table.value(0, 0), Value::Text("unet"));
+        assert_eq!(table.cell(1, 0), "");
+        assert_eq!(table.image_dir(1), path::Path::new("r2"));
+    }
+}
@@ -0,0 +1,448 @@
+use std::cmp::Ordering;
+
+use color_eyre::eyre::{self, OptionExt};
+use regex::Regex;
+
+use crate::dataset::Value;
+use crate::schema::{ColumnType, Schema};
+use crate::sort::compare_natural;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+// The value to compare with, and the type of the column it is compared with
+#[derive(Clone, Debug)]
+pub struct Literal {
+    pub text: String,
+    pub column_type: ColumnType,
+}
+
+impl Literal {
+    // String columns stay text; in other columns, text that doesn't fit the type is read as whatever type it fits
+    fn typed<'a>(&self, text: &'a str) -> Value<'a> {
+        match (self.column_type, self.column_type.parse(text)) {
+            (ColumnType::String, _) => Value::Text(text),
+            (_, Value::Text(_)) => Value::parse(text),
+            (_, value) => value,
+        }
+    }
+}
+
+// A parsed filter expression with its column names already resolved to indices
+#[derive(Clone, Debug)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Compare {
+        column: usize,
+        op: CompareOp,
+        value: Literal,
+    },
+    Matches {
+        column: usize,
+        regex: Regex,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Number(String),
+    Op(CompareOp),
+    Match,
+    NotMatch,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+// Column names that aren't a single word are written in backticks
+pub fn quote_column(name: &str) -> String {
+    let is_word = name.starts_with(|c: char| is_word_char(c) && !c.is_ascii_digit() && c != '-')
+        && name.chars().all(is_word_char);
+    if is_word {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('\\', "\\\\").replace('`', "\\`"))
+    }
+}
+
+fn tokenize(input: &str) -> eyre::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let token = match c {
+            '(' | ')' | '&' | '|' | '=' | '!' | '<' | '>' => {
+                chars.next();
+                let followed_by_eq =
+                    matches!(c, '=' | '!' | '<' | '>') && chars.next_if_eq(&'=').is_some();
+                match (c, followed_by_eq) {
+                    ('(', _) => Token::LParen,
+                    (')', _) => Token::RParen,
+                    ('&', _) if chars.next_if_eq(&'&').is_some() => Token::And,
+                    ('|', _) if chars.next_if_eq(&'|').is_some() => Token::Or,
+                    ('=', true) => Token::Op(CompareOp::Eq),
+                    ('=', false) if chars.next_if_eq(&'~').is_some() => Token::Match,
+                    ('!', true) => Token::Op(CompareOp::Ne),
+                    ('!', false) if chars.next_if_eq(&'~').is_some() => Token::NotMatch,
+                    ('!', false) => Token::Not,
+                    ('<', true) => Token::Op(CompareOp::Le),
+                    ('<', false) => Token::Op(CompareOp::Lt),
+                    ('>', true) => Token::Op(CompareOp::Ge),
+                    ('>', false) => Token::Op(CompareOp::Gt),
+                    _ => return Err(eyre::eyre!("Unexpected operator near \"{c}\"")),
+                }
+            }
+            '"' | '\'' | '`' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        // Only the quote and backslash itself are escaped, so regexes such as `\d` work as written
+                        Some('\\') => match chars.next_if(|&ch| ch == c || ch == '\\') {
+                            Some(ch) => text.push(ch),
+                            None => text.push('\\'),
+                        },
+                        Some(ch) if ch == c => break,
+                        Some(ch) => text.push(ch),
+                        None => return Err(eyre::eyre!("Unterminated string")),
+                    }
+                }
+                if c == '`' {
+                    Token::Word(text)
+                } else {
+                    Token::Quoted(text)
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' => {
+                let mut text = String::new();
+                while let Some(ch) = chars.next_if(|&ch| is_word_char(ch) || ch == '+') {
+                    text.push(ch);
+                }
+                if text.parse::<f64>().is_ok() {
+                    Token::Number(text)
+                } else {
+                    Token::Word(text)
+                }
+            }
+            c if is_word_char(c) => {
+                let mut text = String::new();
+                while let Some(ch) = chars.next_if(|&ch| is_word_char(ch)) {
+                    text.push(ch);
+                }
+                Token::Word(text)
+            }
+            c => return Err(eyre::eyre!("Unexpected character \"{c}\"")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    headers: &'a [String],
+    schema: &'a Schema,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> eyre::Result<Filter> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Filter::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> eyre::Result<Filter> {
+        let mut left = self.parse_not()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            left = Filter::And(Box::new(left), Box::new(self.parse_not()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> eyre::Result<Filter> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.next();
+                Ok(Filter::Not(Box::new(self.parse_not()?)))
+            }
+            Some(Token::LParen) => {
+                self.next();
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err(eyre::eyre!("Missing \")\"")),
+                }
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> eyre::Result<Filter> {
+        let column = match self.next() {
+            Some(Token::Word(name)) => self
+                .headers
+                .iter()
+                .position(|h| *h == name)
+                .ok_or_else(|| eyre::eyre!("Unknown column \"{name}\""))?,
+            Some(token) => return Err(eyre::eyre!("Expected a column name, got {token:?}")),
+            None => return Err(eyre::eyre!("Expected a column name")),
+        };
+        let operator = self
+            .next()
+            .ok_or_eyre("Expected a comparison after the column name")?;
+        let value = match self.next() {
+            Some(Token::Word(text) | Token::Quoted(text) | Token::Number(text)) => text,
+            _ => return Err(eyre::eyre!("Expected a value to compare with")),
+        };
+        match operator {
+            Token::Op(op) => Ok(Filter::Compare {
+                column,
+                op,
+                value: Literal {
+                    text: value,
+                    column_type: self.schema.column_type(column),
+                },
+            }),
+            Token::Match => Ok(Filter::Matches {
+                column,
+                regex: Regex::new(&value)?,
+            }),
+            Token::NotMatch => Ok(Filter::Not(Box::new(Filter::Matches {
+                column,
+                regex: Regex::new(&value)?,
+            }))),
+            token => Err(eyre::eyre!("Expected a comparison, got {token:?}")),
+        }
+    }
+}
+
+impl Filter {
+    // e.g. `model == "unet" && epoch > 10 && loss < 0.2`; comparisons follow the column types in `schema`
+    pub fn parse(input: &str, headers: &[String], schema: &Schema) -> eyre::Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+            headers,
+            schema,
+        };
+        let filter = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(eyre::eyre!("Unexpected {token:?} after the expression"));
+        }
+        Ok(filter)
+    }
+
+    pub fn matches(&self, row: &[String]) -> bool {
+        match self {
+            Filter::And(left, right) => left.matches(row) && right.matches(row),
+            Filter::Or(left, right) => left.matches(row) || right.matches(row),
+            Filter::Not(inner) => !inner.matches(row),
+            Filter::Compare { column, op, value } => {
+                let cell = row.get(*column).map_or("", String::as_str);
+                let ordering = match value.typed(cell).compare(&value.typed(&value.text)) {
+                    Some(ordering) => Some(ordering),
+                    // Ordering values of different types is meaningless
+                    None if !matches!(op, CompareOp::Eq | CompareOp::Ne) => None,
+                    None => Some(compare_natural(cell, &value.text)),
+                };
+                match (op, ordering) {
+                    (CompareOp::Eq, ordering) => ordering == Some(Ordering::Equal),
+                    (CompareOp::Ne, ordering) => ordering != Some(Ordering::Equal),
+                    (_, None) => false,
+                    (CompareOp::Lt, Some(ordering)) => ordering.is_lt(),
+                    (CompareOp::Le, Some(ordering)) => ordering.is_le(),
+                    (CompareOp::Gt, Some(ordering)) => ordering.is_gt(),
+                    (CompareOp::Ge, Some(ordering)) => ordering.is_ge(),
+                }
+            }
+            Filter::Matches { column, regex } => {
+                regex.is_match(row.get(*column).map_or("", String::as_str))
+            }
+        }
+    }
+
+    // Indices of the rows that match
+    pub fn apply(&self, rows: &[Vec<String>]) -> Vec<usize> {
+        rows.iter()
+            .enumerate()
+            .filter_map(|(index, row)| self.matches(row).then_some(index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::TypeOverrides;
+
+    fn headers() -> Vec<String> {
+        ["model", "epoch", "loss", "run name"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect()
+    }
+
+    fn rows() -> Vec<Vec<String>> {
+        [
+            ["unet", "5", "0.30", "first try"],
+            ["unet", "12", "0.15", "long run"],
+            ["resnet", "20", "0.10", "baseline"],
+            ["unet", "30", "0.25", "long run 2"],
+        ]
+        .iter()
+        .map(|row| row.iter().map(|v| v.to_string()).collect())
+        .collect()
+    }
+
+    fn matching(expression: &str) -> Vec<usize> {
+        Filter::parse(expression, &headers(), &Schema::infer(&rows(), 4))
+            .unwrap()
+            .apply(&rows())
+    }
+
+    #[test]
+    fn test_string_equality() {
+        assert_eq!(matching("model == \"unet\""), vec![0, 1, 3]);
+        assert_eq!(matching("model != 'unet'"), vec![2]);
+        assert_eq!(matching("model == resnet"), vec![2]);
+    }
+
+    #[test]
+    fn test_numeric_comparisons() {
+        assert_eq!(matching("epoch > 10"), vec![1, 2, 3]);
+        assert_eq!(matching("epoch >= 12"), vec![1, 2, 3]);
+        assert_eq!(matching("loss < 0.2"), vec![1, 2]);
+        assert_eq!(matching("loss <= 0.15"), vec![1, 2]);
+        assert_eq!(matching("loss == 0.1"), vec![2]);
+    }
+
+    #[test]
+    fn test_typed_comparisons() {
+        let headers: Vec<String> = ["date", "epoch", "ok", "run"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        let rows: Vec<Vec<String>> = [
+            ["2024-01-05", "5", "true", "1e3"],
+            ["2023-12-31", "n/a", "False", "1000"],
+            ["2024-02-01", "12", "", "007"],
+        ]
+        .iter()
+        .map(|row| row.iter().map(|v| v.to_string()).collect())
+        .collect();
+        let overrides = TypeOverrides::from([
+            ("epoch".to_string(), ColumnType::Integer),
+            ("run".to_string(), ColumnType::String),
+        ]);
+        let schema = Schema::infer(&rows, 4).with_overrides(&headers, &overrides);
+        let matching = |expression| {
+            Filter::parse(expression, &headers, &schema)
+                .unwrap()
+                .apply(&rows)
+        };
+
+        assert_eq!(matching("date >= 2024-01-01"), vec![0, 2]);
+        assert_eq!(matching("date < '2024-01-05T12:00:00'"), vec![0, 1]);
+        assert_eq!(matching("epoch > 6"), vec![2]);
+        assert_eq!(matching("epoch == 'n/a'"), vec![1]);
+        assert_eq!(matching("ok == false"), vec![1]);
+        // Not read as numbers, so `1e3` isn't 1000
+        assert_eq!(matching("run == 1000"), vec![1]);
+        assert_eq!(matching("run =~ '^0'"), vec![2]);
+    }
+
+    #[test]
+    fn test_boolean_combinations() {
+        assert_eq!(
+            matching("model == \"unet\" && epoch > 10 && loss < 0.2"),
+            vec![1]
+        );
+        assert_eq!(matching("epoch < 10 || loss < 0.12"), vec![0, 2]);
+        assert_eq!(matching("!(model == unet)"), vec![2]);
+        assert_eq!(
+            matching("model == unet && (epoch < 10 || epoch > 20)"),
+            vec![0, 3]
+        );
+    }
+
+    #[test]
+    fn test_regex() {
+        assert_eq!(matching("`run name` =~ \"^long\""), vec![1, 3]);
+        assert_eq!(matching("`run name` !~ \"\\d$\""), vec![0, 1, 2]);
+        // Doubled backslashes and escaped quotes still work
+        assert_eq!(matching("`run name` =~ \"\\\\s\\\\d\""), vec![3]);
+        assert_eq!(matching("model != 'un\\'et'"), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_err_unknown_column() {
+        assert!(Filter::parse("accuracy > 0.5", &headers(), &Schema::default()).is_err());
+    }
+
+    #[test]
+    fn test_quote_column() {
+        assert_eq!(quote_column("metrics.loss"), "metrics.loss");
+        assert_eq!(quote_column("run name"), "`run name`");
+        assert_eq!(quote_column("a`b"), "`a\\`b`");
+        let expression = format!("{} == 'long run'", quote_column("run name"));
+        assert_eq!(matching(&expression), vec![1]);
+    }
+
+    #[test]
+    fn test_err_malformed_expressions() {
+        let bad = [
+            "",
+            "model",
+            "model ==",
+            "model = unet",
+            "(model == unet",
+            "(= a == 1)",
+            "model == unet &&",
+            "model == unet extra",
+            "model =~ \"(\"",
+            "model == \"unterminated",
+        ];
+
+        for expression in bad {
+            assert!(
+                Filter::parse(expression, &headers(), &Schema::default()).is_err(),
+                "{expression}"
+            );
+        }
+    }
+}
@@ -0,0 +1,546 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, mpsc};
+use std::time::SystemTime;
+use std::{fs, path, thread};
+
+use color_eyre::eyre;
+use ratatui::layout::Rect;
+use ratatui_image::picker::Picker;
+use ratatui_image::protocol::Protocol;
+use ratatui_image::{FontSize, Resize};
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ImageKey {
+    pub path: path::PathBuf,
+    pub mtime: Option<SystemTime>,
+    pub size: (u16, u16),
+}
+
+pub enum CacheEntry {
+    Loading,
+    Ready(Protocol),
+    Failed(String),
+}
+
+struct Slot {
+    entry: CacheEntry,
+    bytes: usize,
+    last_used: u64,
+}
+
+// Jobs for visible panes are always served before prefetch jobs
+#[derive(Default)]
+struct JobQueue {
+    urgent: VecDeque<ImageKey>,
+    prefetch: VecDeque<ImageKey>,
+    closed: bool,
+}
+
+#[derive(Default)]
+struct Jobs {
+    queue: Mutex<JobQueue>,
+    available: Condvar,
+}
+
+impl Jobs {
+    fn lock(&self) -> MutexGuard<'_, JobQueue> {
+        self.queue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn next(&self) -> Option<ImageKey> {
+        let mut queue = self.queue.lock().ok()?;
+        loop {
+            if queue.closed {
+                return None;
+            }
+            if let Some(key) = queue
+                .urgent
+                .pop_front()
+                .or_else(|| queue.prefetch.pop_front())
+            {
+                return Some(key);
+            }
+            queue = self.available.wait(queue).ok()?;
+        }
+    }
+}
+
+struct Decoded {
+    key: ImageKey,
+    entry: CacheEntry,
+    bytes: usize,
+}
+
+pub struct ImageCache {
+    slots: HashMap<ImageKey, Slot>,
+    memory_budget: usize,
+    memory_used: usize,
+    tick: u64,
+    jobs: Arc<Jobs>,
+    result_rx: mpsc::Receiver<Decoded>,
+    // Images that failed to decode since the last `take_failures`
+    failures: Vec<(path::PathBuf, String)>,
+    // Modification times of the images on screen or prefetched, read once while they stay there
+    mtimes: HashMap<path::PathBuf, Option<SystemTime>>,
+}
+
+fn load_protocol(picker: &Picker, key: &ImageKey) -> eyre::Result<Protocol> {
+    let image = image::ImageReader::open(&key.path)?
+        .with_guessed_format()?
+        .decode()?;
+    let (width, height) = key.size;
+    let protocol = picker.new_protocol(image, Rect::new(0, 0, width, height), Resize::default())?;
+    Ok(protocol)
+}
+
+// Approximates the memory held by a protocol as the RGBA pixels it covers
+fn protocol_bytes(protocol: &Protocol, font_size: FontSize) -> usize {
+    let area = protocol.area();
+    let (font_width, font_height) = font_size;
+    area.width as usize * font_width as usize * area.height as usize * font_height as usize * 4
+}
+
+fn spawn_worker(picker: Picker, jobs: Arc<Jobs>, result_tx: mpsc::Sender<Decoded>) {
+    thread::spawn(move || {
+        // `None` means the cache was dropped, so there is nobody left to serve
+        while let Some(key) = jobs.next() {
+            let (entry, bytes) = match load_protocol(&picker, &key) {
+                Ok(protocol) => {
+                    let bytes = protocol_bytes(&protocol, picker.font_size());
+                    (CacheEntry::Ready(protocol), bytes)
+                }
+                Err(err) => (CacheEntry::Failed(err.to_string()), 0),
+            };
+            if result_tx.send(Decoded { key, entry, bytes }).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+impl ImageCache {
+    pub fn new(picker: Picker, memory_budget: usize, workers: usize) -> Self {
+        let jobs = Arc::new(Jobs::default());
+        let (result_tx, result_rx) = mpsc::channel();
+        for _ in 0..workers.max(1) {
+            spawn_worker(picker.clone(), Arc::clone(&jobs), result_tx.clone());
+        }
+        Self {
+            slots: HashMap::new(),
+            memory_budget,
+            memory_used: 0,
+            tick: 0,
+            jobs,
+            result_rx,
+            failures: Vec::new(),
+            mtimes: HashMap::new(),
+        }
+    }
+
+    pub fn default_workers() -> usize {
+        thread::available_parallelism().map_or(2, |n| n.get().min(4))
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn memory_used(&self) -> usize {
+        self.memory_used
+    }
+
+    fn key(&mut self, path: &path::Path, area: Rect) -> ImageKey {
+        let mtime = *self
+            .mtimes
+            .entry(path.to_path_buf())
+            .or_insert_with(|| fs::metadata(path).and_then(|m| m.modified()).ok());
+        ImageKey {
+            path: path.to_path_buf(),
+            mtime,
+            size: (area.width, area.height),
+        }
+    }
+
+    // Images are looked at again on disk, so that changed ones are decoded again
+    pub fn forget_mtimes(&mut self) {
+        self.mtimes.clear();
+    }
+
+    // Returns the entry for the image at `path` fitted to `area`, queueing a decode if needed
+    pub fn get(&mut self, path: &path::Path, area: Rect) -> &CacheEntry {
+        let key = self.key(path, area);
+        let is_new = !self.slots.contains_key(&key);
+        if is_new || matches!(self.slots[&key].entry, CacheEntry::Loading) {
+            let mut queue = self.jobs.lock();
+            // A pending prefetch for this image is now needed right away
+            if let Some(pos) = queue.prefetch.iter().position(|k| *k == key) {
+                queue.prefetch.remove(pos);
+                queue.urgent.push_back(key.clone());
+            } else if is_new {
+                queue.urgent.push_back(key.clone());
+            }
+            self.jobs.available.notify_one();
+        }
+        let slot = self.slots.entry(key).or_insert(Slot {
+            entry: CacheEntry::Loading,
+            bytes: 0,
+            last_used: 0,
+        });
+        slot.last_used = self.tick;
+        &slot.entry
+    }
+
+    // Replaces the pending prefetch jobs, dropping those that are no longer wanted
+    pub fn prefetch(&mut self, requests: impl IntoIterator<Item = (path::PathBuf, Rect)>) {
+        let requests: Vec<ImageKey> = requests
+            .into_iter()
+            .map(|(path, area)| self.key(&path, area))
+            .collect();
+        let mut wanted = HashSet::new();
+        let mut prefetch = VecDeque::new();
+        let mut queue = self.jobs.lock();
+        let stale = std::mem::take(&mut queue.prefetch);
+        // Prefetched images count as used one frame ago, so visible ones outlive them
+        let last_used = self.tick.saturating_sub(1);
+        for key in requests {
+            if !wanted.insert(key.clone()) {
+                continue;
+            }
+            if let Some(slot) = self.slots.get_mut(&key) {
+                slot.last_used = slot.last_used.max(last_used);
+                if !stale.contains(&key) {
+                    continue;
+                }
+            }
+            prefetch.push_back(key);
+        }
+        for key in stale {
+            if !wanted.contains(&key) {
+                self.slots.remove(&key);
+            }
+        }
+        for key in &prefetch {
+            self.slots.entry(key.clone()).or_insert(Slot {
+                entry: CacheEntry::Loading,
+                bytes: 0,
+                last_used,
+            });
+        }
+        queue.prefetch = prefetch;
+        drop(queue);
+        self.jobs.available.notify_all();
+
+        // Images on screen were used since the last poll
+        let kept: HashSet<&path::Path> = self
+            .slots
+            .iter()
+            .filter(|(_, slot)| slot.last_used >= self.tick)
+            .map(|(key, _)| key.path.as_path())
+            .chain(wanted.iter().map(|key| key.path.as_path()))
+            .collect();
+        self.mtimes.retain(|path, _| kept.contains(path.as_path()));
+    }
+
+    pub fn take_failures(&mut self) -> Vec<(path::PathBuf, String)> {
+        std::mem::take(&mut self.failures)
+    }
+
+    // Moves finished decodes into the cache; returns whether anything changed
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(Decoded { key, entry, bytes }) = self.result_rx.try_recv() {
+            if let Some(slot) = self.slots.get_mut(&key) {
+                if let CacheEntry::Failed(err) = &entry {
+                    self.failures.push((key.path.clone(), err.clone()));
+                }
+                slot.entry = entry;
+                slot.bytes = bytes;
+                self.memory_used += bytes;
+                changed = true;
+            }
+        }
+        if changed {
+            self.evict();
+        }
+        self.tick += 1;
+        changed
+    }
+
+    // Images used since the last poll are on screen and are never evicted
+    fn evict(&mut self) {
+        while self.memory_used > self.memory_budget {
+            let oldest = self
+                .slots
+                .iter()
+                .filter(|(_, slot)| !matches!(slot.entry, CacheEntry::Loading))
+                .filter(|(_, slot)| slot.last_used < self.tick)
+                .min_by_key(|(_, slot)| slot.last_used)
+                .map(|(key, _)| key.clone());
+            let Some(slot) = oldest.and_then(|key| self.slots.remove(&key)) else {
+                break;
+            };
+            self.memory_used -= slot.bytes;
+        }
+    }
+}
+
+impl Drop for ImageCache {
+    fn drop(&mut self) {
+        self.jobs.lock().closed = true;
+        self.jobs.available.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    // A 4x4 png fits in a single 8x16 cell, which is accounted as 512 bytes
+    const IMAGE_BYTES: usize = 8 * 16 * 4;
+
+    fn test_cache(memory_budget: usize) -> ImageCache {
+        ImageCache::new(Picker::from_fontsize((8, 16)), memory_budget, 1)
+    }
+
+    // Without workers, queued jobs stay queued
+    fn idle_cache() -> ImageCache {
+        let (_, result_rx) = mpsc::channel();
+        ImageCache {
+            slots: HashMap::new(),
+            memory_budget: IMAGE_BYTES,
+            memory_used: 0,
+            tick: 0,
+            jobs: Arc::default(),
+            result_rx,
+            failures: Vec::new(),
+            mtimes: HashMap::new(),
+        }
+    }
+
+    fn wait_for_results(cache: &mut ImageCache) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !cache.poll() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn write_pngs(dir: &path::Path, count: usize) -> Vec<path::PathBuf> {
+        (0..count)
+            .map(|i| {
+                let path = dir.join(format!("image{i}.png"));
+                image::RgbImage::new(4, 4).save(&path).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_loading_then_ready() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefiles = write_pngs(tmpdir.path(), 1);
+        let area = Rect::new(0, 0, 10, 5);
+
+        let mut cache = test_cache(IMAGE_BYTES);
+        assert!(matches!(
+            cache.get(&imagefiles[0], area),
+            CacheEntry::Loading
+        ));
+
+        wait_for_results(&mut cache);
+        assert!(matches!(
+            cache.get(&imagefiles[0], area),
+            CacheEntry::Ready(_)
+        ));
+        assert_eq!(cache.memory_used(), IMAGE_BYTES);
+    }
+
+    #[test]
+    fn test_changed_image_after_forget_mtimes() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefiles = write_pngs(tmpdir.path(), 1);
+        let area = Rect::new(0, 0, 10, 5);
+
+        let mut cache = test_cache(4 * IMAGE_BYTES);
+        cache.get(&imagefiles[0], area);
+        wait_for_results(&mut cache);
+        let modified = SystemTime::now() + Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&imagefiles[0])
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        // The file isn't looked at again on every frame
+        assert!(matches!(
+            cache.get(&imagefiles[0], area),
+            CacheEntry::Ready(_)
+        ));
+        cache.forget_mtimes();
+        assert!(matches!(
+            cache.get(&imagefiles[0], area),
+            CacheEntry::Loading
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_image_fails() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefile = tmpdir.path().join("image.png");
+        fs::write(&imagefile, b"not a png").unwrap();
+        let area = Rect::new(0, 0, 10, 5);
+
+        let mut cache = test_cache(IMAGE_BYTES);
+        cache.get(&imagefile, area);
+
+        wait_for_results(&mut cache);
+        assert!(matches!(cache.get(&imagefile, area), CacheEntry::Failed(_)));
+        let failures = cache.take_failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, imagefile);
+        assert!(cache.take_failures().is_empty());
+    }
+
+    #[test]
+    fn test_size_is_part_of_key() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefiles = write_pngs(tmpdir.path(), 1);
+
+        let mut cache = test_cache(4 * IMAGE_BYTES);
+        cache.get(&imagefiles[0], Rect::new(0, 0, 10, 5));
+        wait_for_results(&mut cache);
+
+        assert!(matches!(
+            cache.get(&imagefiles[0], Rect::new(0, 0, 20, 10)),
+            CacheEntry::Loading
+        ));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefiles = write_pngs(tmpdir.path(), 3);
+        let area = Rect::new(0, 0, 10, 5);
+
+        let mut cache = test_cache(2 * IMAGE_BYTES);
+        for imagefile in &imagefiles[..2] {
+            cache.get(imagefile, area);
+            wait_for_results(&mut cache);
+        }
+        cache.get(&imagefiles[0], area);
+        cache.get(&imagefiles[2], area);
+        wait_for_results(&mut cache);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.memory_used(), 2 * IMAGE_BYTES);
+        assert!(matches!(
+            cache.get(&imagefiles[0], area),
+            CacheEntry::Ready(_)
+        ));
+        assert!(matches!(
+            cache.get(&imagefiles[1], area),
+            CacheEntry::Loading
+        ));
+    }
+
+    #[test]
+    fn test_visible_images_outlive_budget() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefiles = write_pngs(tmpdir.path(), 2);
+        let area = Rect::new(0, 0, 10, 5);
+
+        let mut cache = test_cache(0);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            let both_ready = imagefiles
+                .iter()
+                .all(|f| matches!(cache.get(f, area), CacheEntry::Ready(_)));
+            if both_ready {
+                break;
+            }
+            cache.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(cache.memory_used(), 2 * IMAGE_BYTES);
+    }
+
+    #[test]
+    fn test_prefetched_image_is_ready() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefiles = write_pngs(tmpdir.path(), 1);
+        let area = Rect::new(0, 0, 10, 5);
+
+        let mut cache = test_cache(IMAGE_BYTES);
+        cache.prefetch([(imagefiles[0].clone(), area)]);
+        wait_for_results(&mut cache);
+
+        assert!(matches!(
+            cache.get(&imagefiles[0], area),
+            CacheEntry::Ready(_)
+        ));
+    }
+
+    #[test]
+    fn test_wanted_prefetch_outlives_older_images() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefiles = write_pngs(tmpdir.path(), 3);
+        let (neighbour, previous, visible) = (&imagefiles[0], &imagefiles[1], &imagefiles[2]);
+        let area = Rect::new(0, 0, 10, 5);
+
+        let mut cache = test_cache(2 * IMAGE_BYTES);
+        cache.prefetch([(neighbour.clone(), area)]);
+        wait_for_results(&mut cache);
+        cache.get(previous, area);
+        wait_for_results(&mut cache);
+        cache.poll();
+        // The neighbour was decoded first, but is still wanted while the previous image isn't
+        cache.get(visible, area);
+        cache.prefetch([(neighbour.clone(), area)]);
+        wait_for_results(&mut cache);
+
+        assert_eq!(cache.len(), 2);
+        assert!(matches!(cache.get(neighbour, area), CacheEntry::Ready(_)));
+        assert!(matches!(cache.get(previous, area), CacheEntry::Loading));
+    }
+
+    #[test]
+    fn test_prefetch_drops_stale_jobs() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefiles = write_pngs(tmpdir.path(), 2);
+        let area = Rect::new(0, 0, 10, 5);
+
+        let mut cache = idle_cache();
+        cache.prefetch([(imagefiles[0].clone(), area)]);
+        cache.prefetch([(imagefiles[1].clone(), area)]);
+
+        assert_eq!(cache.len(), 1);
+        let queue = cache.jobs.lock();
+        assert_eq!(queue.prefetch.len(), 1);
+        assert_eq!(queue.prefetch[0].path, imagefiles[1]);
+    }
+
+    #[test]
+    fn test_get_promotes_prefetch_job() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let imagefiles = write_pngs(tmpdir.path(), 1);
+        let area = Rect::new(0, 0, 10, 5);
+
+        let mut cache = idle_cache();
+        cache.prefetch([(imagefiles[0].clone(), area)]);
+        cache.get(&imagefiles[0], area);
+
+        let queue = cache.jobs.lock();
+        assert!(queue.prefetch.is_empty());
+        assert_eq!(queue.urgent.len(), 1);
+    }
+}
@@ -23,6 +23,21 @@ pub fn collect_basenames(imgdir_paths: &Vec<path::PathBuf>) -> Vec<String> {
     basenames.into_iter().collect()
 }
 
+// Which of `basenames` exist as files directly in `dir`
+pub fn presence(dir: &path::Path, basenames: &[String]) -> Vec<bool> {
+    let present: std::collections::HashSet<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    basenames
+        .iter()
+        .map(|basename| present.contains(basename))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -136,4 +151,26 @@ mod tests {
 
         assert!(result.is_empty());
     }
+
+    #[test]
+    fn test_presence() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp_dir_path = temp_dir.path().to_path_buf();
+        fs::write(temp_dir_path.join("image1.jpg"), b"").unwrap();
+        fs::create_dir(temp_dir_path.join("image2.jpg")).unwrap();
+        let basenames = vec![
+            "image1.jpg".to_string(),
+            "image2.jpg".to_string(),
+            "image3.jpg".to_string(),
+        ];
+
+        assert_eq!(
+            presence(&temp_dir_path, &basenames),
+            vec![true, false, false]
+        );
+        assert_eq!(
+            presence(&temp_dir_path.join("missing"), &basenames),
+            vec![false, false, false]
+        );
+    }
 }
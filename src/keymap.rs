@@ -0,0 +1,696 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use color_eyre::eyre;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::Tab;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Quit,
+    NextTab,
+    RowUp,
+    RowDown,
+    PageUp,
+    PageDown,
+    FirstRow,
+    LastRow,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Filter,
+    CommandLine,
+    Help,
+    ColumnLeft,
+    ColumnRight,
+    NarrowColumn,
+    WidenColumn,
+    FreezeColumns,
+    Sort,
+    AddSortKey,
+    ClearSort,
+    PaneLeft,
+    PaneDown,
+    PaneUp,
+    PaneRight,
+    SplitRight,
+    SplitDown,
+    ClosePane,
+    ShrinkPaneWidth,
+    GrowPaneWidth,
+    ShrinkPaneHeight,
+    GrowPaneHeight,
+    NextImage,
+    PreviousImage,
+    SortByCoverage,
+    ShowImage,
+    LoadReport,
+    Reload,
+}
+
+const ACTIONS: &[(Action, &str, &str)] = &[
+    (Action::Quit, "quit", "Quit"),
+    (Action::NextTab, "next-tab", "Switch to the next tab"),
+    (Action::RowUp, "row-up", "Previous row"),
+    (Action::RowDown, "row-down", "Next row"),
+    (Action::PageUp, "page-up", "Up one page of rows"),
+    (Action::PageDown, "page-down", "Down one page of rows"),
+    (Action::FirstRow, "first-row", "First row"),
+    (Action::LastRow, "last-row", "Last row"),
+    (Action::Search, "search", "Search cells"),
+    (
+        Action::NextMatch,
+        "next-match",
+        "Next row matching the search",
+    ),
+    (
+        Action::PreviousMatch,
+        "previous-match",
+        "Previous row matching the search",
+    ),
+    (Action::Filter, "filter", "Filter rows with an expression"),
+    (Action::CommandLine, "command-line", "Open the command line"),
+    (Action::Help, "help", "Show the key bindings"),
+    (
+        Action::ColumnLeft,
+        "column-left",
+        "Move the column cursor left",
+    ),
+    (
+        Action::ColumnRight,
+        "column-right",
+        "Move the column cursor right",
+    ),
+    (
+        Action::NarrowColumn,
+        "narrow-column",
+        "Narrow the focused column",
+    ),
+    (
+        Action::WidenColumn,
+        "widen-column",
+        "Widen the focused column",
+    ),
+    (
+        Action::FreezeColumns,
+        "freeze-columns",
+        "Freeze columns up to the focused one",
+    ),
+    (Action::Sort, "sort", "Sort by the focused column"),
+    (
+        Action::AddSortKey,
+        "add-sort-key",
+        "Add the focused column as a sort key",
+    ),
+    (Action::ClearSort, "clear-sort", "Restore the file order"),
+    (Action::PaneLeft, "pane-left", "Focus the pane to the left"),
+    (Action::PaneDown, "pane-down", "Focus the pane below"),
+    (Action::PaneUp, "pane-up", "Focus the pane above"),
+    (
+        Action::PaneRight,
+        "pane-right",
+        "Focus the pane to the right",
+    ),
+    (
+        Action::SplitRight,
+        "split-right",
+        "Split the focused pane side by side",
+    ),
+    (
+        Action::SplitDown,
+        "split-down",
+        "Split the focused pane top and bottom",
+    ),
+    (Action::ClosePane, "close-pane", "Close the focused pane"),
+    (
+        Action::ShrinkPaneWidth,
+        "shrink-pane-width",
+        "Make the focused pane narrower",
+    ),
+    (
+        Action::GrowPaneWidth,
+        "grow-pane-width",
+        "Make the focused pane wider",
+    ),
+    (
+        Action::ShrinkPaneHeight,
+        "shrink-pane-height",
+        "Make the focused pane shorter",
+    ),
+    (
+        Action::GrowPaneHeight,
+        "grow-pane-height",
+        "Make the focused pane taller",
+    ),
+    (
+        Action::NextImage,
+        "next-image",
+        "Show the next image in the focused pane",
+    ),
+    (
+        Action::PreviousImage,
+        "previous-image",
+        "Show the previous image in the focused pane",
+    ),
+    (
+        Action::SortByCoverage,
+        "sort-by-coverage",
+        "Sort rows by how many images they have",
+    ),
+    (
+        Action::ShowImage,
+        "show-image",
+        "Show the selected image in the Image tab",
+    ),
+    (
+        Action::LoadReport,
+        "load-report",
+        "Show the rows skipped while loading",
+    ),
+    (Action::Reload, "reload", "Load the data again"),
+];
+
+impl Action {
+    pub fn all() -> impl Iterator<Item = Action> {
+        ACTIONS.iter().map(|(action, _, _)| *action)
+    }
+
+    pub fn name(&self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(action, _, _)| action == self)
+            .map_or("", |(_, name, _)| name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(action, _, _)| action == self)
+            .map_or("", |(_, _, description)| description)
+    }
+}
+
+impl FromStr for Action {
+    type Err = eyre::Report;
+
+    fn from_str(name: &str) -> eyre::Result<Self> {
+        ACTIONS
+            .iter()
+            .find(|(_, n, _)| *n == name)
+            .map(|(action, _, _)| *action)
+            .ok_or_else(|| eyre::eyre!("Unknown action \"{name}\""))
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// A key together with its modifiers, e.g. `ctrl+d` or `G`.
+// Shift is folded into the character for printable keys, so `shift+g` and `G` are the same chord.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::BackTab, "BackTab"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Esc, "Esc"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Char(' '), "Space"),
+];
+
+const MODIFIER_NAMES: &[(KeyModifiers, &str)] = &[
+    (KeyModifiers::CONTROL, "ctrl"),
+    (KeyModifiers::ALT, "alt"),
+    (KeyModifiers::SHIFT, "shift"),
+];
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let (code, modifiers) = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => (
+                KeyCode::Char(c.to_uppercase().next().unwrap_or(c)),
+                modifiers - KeyModifiers::SHIFT,
+            ),
+            KeyCode::BackTab => (code, modifiers - KeyModifiers::SHIFT),
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                (KeyCode::BackTab, modifiers - KeyModifiers::SHIFT)
+            }
+            _ => (code, modifiers),
+        };
+        // Only these modifiers are distinguishable across terminals
+        let modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        Self { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = eyre::Report;
+
+    fn from_str(text: &str) -> eyre::Result<Self> {
+        // The last `+` separates the key, unless the key itself is `+`
+        let (prefix, key) = match text.strip_suffix('+') {
+            Some(rest) if rest.is_empty() || rest.ends_with('+') => (rest, "+"),
+            _ => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for name in prefix.split('+').filter(|name| !name.is_empty()) {
+            modifiers |= MODIFIER_NAMES
+                .iter()
+                .find(|(_, n)| n.eq_ignore_ascii_case(name))
+                .map(|(modifier, _)| *modifier)
+                .ok_or_else(|| eyre::eyre!("Unknown modifier \"{name}\" in \"{text}\""))?;
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => KEY_NAMES
+                .iter()
+                .find(|(_, n)| n.eq_ignore_ascii_case(key))
+                .map(|(code, _)| *code)
+                .or_else(|| {
+                    let number = key.strip_prefix(['F', 'f'])?.parse::<u8>().ok()?;
+                    (1..=12).contains(&number).then_some(KeyCode::F(number))
+                })
+                .ok_or_else(|| eyre::eyre!("Unknown key \"{key}\" in \"{text}\""))?,
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in MODIFIER_NAMES {
+            if self.modifiers.contains(*modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+        match KEY_NAMES.iter().find(|(code, _)| *code == self.code) {
+            Some((_, name)) => f.write_str(name),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{c}"),
+                KeyCode::F(number) => write!(f, "F{number}"),
+                code => write!(f, "{code:?}"),
+            },
+        }
+    }
+}
+
+// Chords pressed one after another, written space-separated, e.g. `g g`
+pub fn parse_sequence(text: &str) -> eyre::Result<Vec<KeyChord>> {
+    let sequence = text
+        .split_whitespace()
+        .map(KeyChord::from_str)
+        .collect::<eyre::Result<Vec<_>>>()?;
+    if sequence.is_empty() {
+        return Err(eyre::eyre!("Empty key sequence"));
+    }
+    Ok(sequence)
+}
+
+pub fn format_sequence(sequence: &[KeyChord]) -> String {
+    sequence
+        .iter()
+        .map(KeyChord::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Bindings in a tab's context take precedence over global ones
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum KeyContext {
+    Global,
+    Data,
+    Image,
+    Coverage,
+}
+
+impl KeyContext {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyContext::Global => "global",
+            KeyContext::Data => "data",
+            KeyContext::Image => "image",
+            KeyContext::Coverage => "coverage",
+        }
+    }
+}
+
+impl From<Tab> for KeyContext {
+    fn from(tab: Tab) -> Self {
+        match tab {
+            Tab::Data => KeyContext::Data,
+            Tab::Image => KeyContext::Image,
+            Tab::Coverage => KeyContext::Coverage,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub context: KeyContext,
+    pub keys: Vec<KeyChord>,
+    pub action: Action,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Lookup {
+    Action(Action),
+    // The keys so far start a longer binding
+    Pending,
+    Unbound,
+}
+
+// Key bindings per context, keyed by action name as they appear in the config file
+pub type KeyOverrides = BTreeMap<String, String>;
+
+// Action name that removes a default binding
+const UNBIND: &str = "none";
+
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+const DEFAULT_BINDINGS: &[(KeyContext, &str, Action)] = &[
+    (KeyContext::Global, "q", Action::Quit),
+    (KeyContext::Global, "Tab", Action::NextTab),
+    (KeyContext::Global, "Up", Action::RowUp),
+    (KeyContext::Global, "Down", Action::RowDown),
+    (KeyContext::Global, "PageUp", Action::PageUp),
+    (KeyContext::Global, "PageDown", Action::PageDown),
+    (KeyContext::Global, "Home", Action::FirstRow),
+    (KeyContext::Global, "g g", Action::FirstRow),
+    (KeyContext::Global, "End", Action::LastRow),
+    (KeyContext::Global, "G", Action::LastRow),
+    (KeyContext::Global, "/", Action::Search),
+    (KeyContext::Global, "n", Action::NextMatch),
+    (KeyContext::Global, "N", Action::PreviousMatch),
+    (KeyContext::Global, "F", Action::Filter),
+    (KeyContext::Global, ":", Action::CommandLine),
+    (KeyContext::Global, "?", Action::Help),
+    (KeyContext::Global, "R", Action::LoadReport),
+    (KeyContext::Global, "ctrl+r", Action::Reload),
+    (KeyContext::Data, "h", Action::ColumnLeft),
+    (KeyContext::Data, "l", Action::ColumnRight),
+    (KeyContext::Data, "a", Action::NarrowColumn),
+    (KeyContext::Data, "d", Action::WidenColumn),
+    (KeyContext::Data, "f", Action::FreezeColumns),
+    (KeyContext::Data, "s", Action::Sort),
+    (KeyContext::Data, "S", Action::AddSortKey),
+    (KeyContext::Data, "u", Action::ClearSort),
+    (KeyContext::Image, "h", Action::PaneLeft),
+    (KeyContext::Image, "j", Action::PaneDown),
+    (KeyContext::Image, "k", Action::PaneUp),
+    (KeyContext::Image, "l", Action::PaneRight),
+    (KeyContext::Image, "r", Action::SplitRight),
+    (KeyContext::Image, "b", Action::SplitDown),
+    (KeyContext::Image, "x", Action::ClosePane),
+    (KeyContext::Image, "a", Action::ShrinkPaneWidth),
+    (KeyContext::Image, "d", Action::GrowPaneWidth),
+    (KeyContext::Image, "w", Action::ShrinkPaneHeight),
+    (KeyContext::Image, "s", Action::GrowPaneHeight),
+    (KeyContext::Image, "]", Action::NextImage),
+    (KeyContext::Image, "[", Action::PreviousImage),
+    (KeyContext::Image, "ä", Action::NextImage),
+    (KeyContext::Image, "ö", Action::PreviousImage),
+    (KeyContext::Coverage, "h", Action::ColumnLeft),
+    (KeyContext::Coverage, "l", Action::ColumnRight),
+    (KeyContext::Coverage, "s", Action::SortByCoverage),
+    (KeyContext::Coverage, "Enter", Action::ShowImage),
+];
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .map(|&(context, keys, action)| Binding {
+                context,
+                keys: parse_sequence(keys).expect("default bindings are valid"),
+                action,
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    // The default bindings with the config file's bindings applied on top
+    pub fn with_overrides(overrides: &[(KeyContext, &KeyOverrides)]) -> eyre::Result<Self> {
+        let mut keymap = Self::default();
+        for (context, keys) in overrides {
+            for (sequence, action) in keys.iter() {
+                let sequence = parse_sequence(sequence)
+                    .map_err(|err| eyre::eyre!("[keys.{}]: {err}", context.name()))?;
+                if action == UNBIND {
+                    keymap.unbind(*context, &sequence);
+                } else {
+                    let action = action
+                        .parse()
+                        .map_err(|err| eyre::eyre!("[keys.{}]: {err}", context.name()))?;
+                    keymap.bind(*context, sequence, action);
+                }
+            }
+        }
+        Ok(keymap)
+    }
+
+    pub fn bind(&mut self, context: KeyContext, keys: Vec<KeyChord>, action: Action) {
+        match self
+            .bindings
+            .iter_mut()
+            .find(|binding| binding.context == context && binding.keys == keys)
+        {
+            Some(binding) => binding.action = action,
+            None => self.bindings.push(Binding {
+                context,
+                keys,
+                action,
+            }),
+        }
+    }
+
+    pub fn unbind(&mut self, context: KeyContext, keys: &[KeyChord]) {
+        self.bindings
+            .retain(|binding| binding.context != context || binding.keys != keys);
+    }
+
+    fn lookup_in(&self, context: KeyContext, keys: &[KeyChord]) -> Lookup {
+        let mut lookup = Lookup::Unbound;
+        for binding in self.bindings.iter().filter(|b| b.context == context) {
+            if binding.keys == keys {
+                return Lookup::Action(binding.action);
+            }
+            if binding.keys.starts_with(keys) {
+                lookup = Lookup::Pending;
+            }
+        }
+        lookup
+    }
+
+    pub fn lookup(&self, tab: Tab, keys: &[KeyChord]) -> Lookup {
+        match self.lookup_in(tab.into(), keys) {
+            Lookup::Unbound => self.lookup_in(KeyContext::Global, keys),
+            lookup => lookup,
+        }
+    }
+
+    // Every action reachable by keys on `tab`, with all of its key sequences, in binding order.
+    // Global bindings shadowed by the tab's own are left out.
+    pub fn active_bindings(&self, tab: Tab) -> Vec<(Action, Vec<Vec<KeyChord>>)> {
+        let context = KeyContext::from(tab);
+        let mut active: Vec<(Action, Vec<Vec<KeyChord>>)> = Vec::new();
+        for binding in self.bindings.iter().filter(|binding| {
+            (binding.context == context || binding.context == KeyContext::Global)
+                && self.lookup(tab, &binding.keys) == Lookup::Action(binding.action)
+        }) {
+            match active
+                .iter_mut()
+                .find(|(action, _)| *action == binding.action)
+            {
+                Some((_, keys)) => keys.push(binding.keys.clone()),
+                None => active.push((binding.action, vec![binding.keys.clone()])),
+            }
+        }
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(text: &str) -> KeyChord {
+        text.parse().unwrap()
+    }
+
+    fn keys(text: &str) -> Vec<KeyChord> {
+        parse_sequence(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_chords() {
+        assert_eq!(
+            chord("ctrl+d"),
+            KeyChord::new(KeyCode::Char('d'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            chord("pagedown"),
+            KeyChord::new(KeyCode::PageDown, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            chord("F5"),
+            KeyChord::new(KeyCode::F(5), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            chord("alt++"),
+            KeyChord::new(KeyCode::Char('+'), KeyModifiers::ALT)
+        );
+        assert_eq!(
+            chord("+"),
+            KeyChord::new(KeyCode::Char('+'), KeyModifiers::NONE)
+        );
+    }
+
+    #[test]
+    fn test_shift_is_folded_into_characters() {
+        assert_eq!(chord("shift+g"), chord("G"));
+        assert_eq!(
+            KeyChord::from(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            chord("G")
+        );
+        assert_eq!(chord("shift+Tab"), chord("BackTab"));
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for text in ["ctrl+d", "G", "PageUp", "alt+Space", "F12", "ä"] {
+            assert_eq!(chord(text).to_string(), text);
+        }
+        assert_eq!(format_sequence(&keys("g  g")), "g g");
+    }
+
+    #[test]
+    fn test_err_invalid_chords() {
+        assert!("hyper+x".parse::<KeyChord>().is_err());
+        assert!("ctrl+nope".parse::<KeyChord>().is_err());
+        assert!(parse_sequence(" ").is_err());
+    }
+
+    #[test]
+    fn test_action_names_round_trip() {
+        for action in Action::all() {
+            assert_eq!(action.name().parse::<Action>().unwrap(), action);
+        }
+        assert!("fly".parse::<Action>().is_err());
+    }
+
+    #[test]
+    fn test_tab_context_takes_precedence() {
+        let keymap = Keymap::default();
+
+        assert_eq!(
+            keymap.lookup(Tab::Data, &keys("h")),
+            Lookup::Action(Action::ColumnLeft)
+        );
+        assert_eq!(
+            keymap.lookup(Tab::Image, &keys("h")),
+            Lookup::Action(Action::PaneLeft)
+        );
+        assert_eq!(
+            keymap.lookup(Tab::Image, &keys("q")),
+            Lookup::Action(Action::Quit)
+        );
+        assert_eq!(keymap.lookup(Tab::Data, &keys("z")), Lookup::Unbound);
+    }
+
+    #[test]
+    fn test_sequences() {
+        let keymap = Keymap::default();
+
+        assert_eq!(keymap.lookup(Tab::Data, &keys("g")), Lookup::Pending);
+        assert_eq!(
+            keymap.lookup(Tab::Data, &keys("g g")),
+            Lookup::Action(Action::FirstRow)
+        );
+        assert_eq!(keymap.lookup(Tab::Data, &keys("g x")), Lookup::Unbound);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let image: KeyOverrides = [
+            ("ctrl+n".to_string(), "next-image".to_string()),
+            ("]".to_string(), "none".to_string()),
+            ("s".to_string(), "split-down".to_string()),
+        ]
+        .into();
+        let keymap = Keymap::with_overrides(&[(KeyContext::Image, &image)]).unwrap();
+
+        assert_eq!(
+            keymap.lookup(Tab::Image, &keys("ctrl+n")),
+            Lookup::Action(Action::NextImage)
+        );
+        assert_eq!(keymap.lookup(Tab::Image, &keys("]")), Lookup::Unbound);
+        assert_eq!(
+            keymap.lookup(Tab::Image, &keys("s")),
+            Lookup::Action(Action::SplitDown)
+        );
+        assert_eq!(
+            keymap.lookup(Tab::Data, &keys("s")),
+            Lookup::Action(Action::Sort)
+        );
+    }
+
+    #[test]
+    fn test_active_bindings() {
+        let data: KeyOverrides = [("n".to_string(), "sort".to_string())].into();
+        let keymap = Keymap::with_overrides(&[(KeyContext::Data, &data)]).unwrap();
+        let active = keymap.active_bindings(Tab::Data);
+        let keys_of = |action: Action| {
+            active
+                .iter()
+                .find(|(a, _)| *a == action)
+                .map(|(_, keys)| keys.iter().map(|k| format_sequence(k)).collect::<Vec<_>>())
+        };
+
+        assert_eq!(keys_of(Action::FirstRow).unwrap(), vec!["Home", "g g"]);
+        assert_eq!(keys_of(Action::Sort).unwrap(), vec!["s", "n"]);
+        assert_eq!(keys_of(Action::NextMatch), None);
+        assert_eq!(keys_of(Action::PaneLeft), None);
+        assert_eq!(keymap.active_bindings(Tab::Image)[0].0, Action::Quit);
+    }
+
+    #[test]
+    fn test_err_invalid_overrides() {
+        let unknown: KeyOverrides = [("x".to_string(), "fly".to_string())].into();
+        let bad_key: KeyOverrides = [("hyper+x".to_string(), "quit".to_string())].into();
+
+        assert!(Keymap::with_overrides(&[(KeyContext::Global, &unknown)]).is_err());
+        assert!(Keymap::with_overrides(&[(KeyContext::Global, &bad_key)]).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{self, WrapErr};
+use ratatui::layout;
+use serde::{Deserialize, Serialize};
+
+use crate::Pane;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SavedDirection {
+    Horizontal,
+    Vertical,
+}
+
+// A pane tree that refers to images by basename, so it can be reused with other datasets
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SavedPane {
+    Leaf {
+        image: String,
+    },
+    Split {
+        direction: SavedDirection,
+        pct: u8,
+        first: Box<SavedPane>,
+        second: Box<SavedPane>,
+    },
+}
+
+impl SavedPane {
+    pub fn from_pane(pane: &Pane, basenames: &[String]) -> Self {
+        match pane {
+            Pane::Leaf { image_id } => SavedPane::Leaf {
+                image: basenames.get(*image_id).cloned().unwrap_or_default(),
+            },
+            Pane::Split {
+                direction,
+                pct,
+                first,
+                second,
+            } => SavedPane::Split {
+                direction: match direction {
+                    layout::Direction::Horizontal => SavedDirection::Horizontal,
+                    layout::Direction::Vertical => SavedDirection::Vertical,
+                },
+                pct: *pct,
+                first: Box::new(Self::from_pane(first, basenames)),
+                second: Box::new(Self::from_pane(second, basenames)),
+            },
+        }
+    }
+
+    // Images missing from `basenames` fall back to the first one
+    pub fn to_pane(&self, basenames: &[String]) -> Pane {
+        match self {
+            SavedPane::Leaf { image } => Pane::Leaf {
+                image_id: basenames.iter().position(|b| b == image).unwrap_or(0),
+            },
+            SavedPane::Split {
+                direction,
+                pct,
+                first,
+                second,
+            } => Pane::Split {
+                direction: match direction {
+                    SavedDirection::Horizontal => layout::Direction::Horizontal,
+                    SavedDirection::Vertical => layout::Direction::Vertical,
+                },
+                pct: (*pct).clamp(5, 95),
+                first: Box::new(first.to_pane(basenames)),
+                second: Box::new(second.to_pane(basenames)),
+            },
+        }
+    }
+}
+
+// Named pane layouts, kept in `$XDG_DATA_HOME/pixmux/layouts.toml`
+#[derive(Clone, Debug, Default)]
+pub struct LayoutStore {
+    path: Option<PathBuf>,
+    pub layouts: BTreeMap<String, SavedPane>,
+    // The file exists but couldn't be read, so writing it would lose the layouts in it
+    unreadable: bool,
+}
+
+impl LayoutStore {
+    // A missing file is an empty store
+    pub fn open(path: PathBuf) -> eyre::Result<Self> {
+        let layouts = match std::fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text)
+                .wrap_err_with(|| format!("Invalid layouts file {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("Could not read layouts file {}", path.display()));
+            }
+        };
+        Ok(Self {
+            path: Some(path),
+            layouts,
+            unreadable: false,
+        })
+    }
+
+    // Stands in for a file that couldn't be opened; layouts saved to it are only kept in memory
+    pub fn unreadable(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            layouts: BTreeMap::new(),
+            unreadable: true,
+        }
+    }
+
+    pub fn open_default() -> eyre::Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::open(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn default_path() -> Option<PathBuf> {
+        Some(
+            crate::config::xdg_home("XDG_DATA_HOME", ".local/share")?
+                .join("pixmux")
+                .join("layouts.toml"),
+        )
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.layouts.keys().map(String::as_str).collect()
+    }
+
+    pub fn get(&self, name: &str) -> eyre::Result<&SavedPane> {
+        self.layouts
+            .get(name)
+            .ok_or_else(|| eyre::eyre!("No layout named \"{name}\""))
+    }
+
+    pub fn insert(&mut self, name: &str, layout: SavedPane) -> eyre::Result<()> {
+        self.layouts.insert(name.to_string(), layout);
+        self.save()
+    }
+
+    pub fn remove(&mut self, name: &str) -> eyre::Result<()> {
+        self.layouts
+            .remove(name)
+            .ok_or_else(|| eyre::eyre!("No layout named \"{name}\""))?;
+        self.save()
+    }
+
+    fn save(&self) -> eyre::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if self.unreadable {
+            eyre::bail!(
+                "Layouts file {} couldn't be read, so changes are kept until pixmux exits",
+                path.display()
+            );
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string(&self.layouts)?)
+            .wrap_err_with(|| format!("Could not write layouts file {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn basenames() -> Vec<String> {
+        ["img.png", "mask.png"]
+            .iter()
+            .map(|b| b.to_string())
+            .collect()
+    }
+
+    fn grid() -> Pane {
+        let mut pane = Pane::default();
+        let second = pane
+            .split_leaf_at(&[], layout::Direction::Horizontal)
+            .unwrap();
+        pane.cycle_image(&second, 2, crate::AdjustDirection::Next)
+            .unwrap();
+        pane.split_leaf_at(&second, layout::Direction::Vertical)
+            .unwrap();
+        pane
+    }
+
+    #[test]
+    fn test_round_trip_by_basename() {
+        let saved = SavedPane::from_pane(&grid(), &basenames());
+        let reordered = vec!["mask.png".to_string(), "img.png".to_string()];
+        let pane = saved.to_pane(&reordered);
+
+        assert_eq!(SavedPane::from_pane(&pane, &reordered), saved);
+        assert!(matches!(
+            pane.get_node_at(&[true]).unwrap(),
+            Pane::Leaf { image_id: 1 }
+        ));
+    }
+
+    #[test]
+    fn test_store_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("layouts.toml");
+        let saved = SavedPane::from_pane(&grid(), &basenames());
+
+        let mut store = LayoutStore::open(path.clone()).unwrap();
+        assert!(store.names().is_empty());
+        store.insert("grid", saved.clone()).unwrap();
+
+        let mut reopened = LayoutStore::open(path.clone()).unwrap();
+        assert_eq!(reopened.names(), vec!["grid"]);
+        assert_eq!(reopened.get("grid").unwrap(), &saved);
+
+        reopened.remove("grid").unwrap();
+        assert!(LayoutStore::open(path).unwrap().names().is_empty());
+    }
+
+    #[test]
+    fn test_err_unknown_layout() {
+        let mut store = LayoutStore::default();
+
+        assert!(store.get("nope").is_err());
+        assert!(store.remove("nope").is_err());
+    }
+
+    #[test]
+    fn test_err_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("layouts.toml");
+        std::fs::write(&path, "grid = { type = \"triangle\" }").unwrap();
+
+        assert!(LayoutStore::open(path.clone()).is_err());
+
+        let mut store = LayoutStore::unreadable(Some(path.clone()));
+        assert!(store.remove("grid").is_err());
+        assert!(
+            store
+                .insert("grid", SavedPane::from_pane(&grid(), &basenames()))
+                .is_err()
+        );
+        assert_eq!(store.names(), vec!["grid"]);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "grid = { type = \"triangle\" }"
+        );
+    }
+}
@@ -1,10 +1,39 @@
 pub mod adjust_index;
+mod app;
+pub mod check;
+pub mod columns;
+pub mod command;
+pub mod config;
+pub mod coverage;
+pub mod dataset;
+pub mod filter;
+pub mod image_cache;
 pub mod imagefile;
+pub mod keymap;
+pub mod layouts;
 pub mod panes;
 pub mod parse_csv;
+pub mod parse_jsonl;
+pub mod parse_parquet;
+pub mod parse_sqlite;
+pub mod schema;
+pub mod search;
+pub mod sort;
+pub mod source;
 pub mod tab;
+pub mod tui;
+mod ui;
 
-pub use adjust_index::{AdjustDirection, cycle_index, step_index};
+pub use adjust_index::{
+    AdjustDirection, cycle_index, neighbour_indices, page_index, scroll_offset, step_index,
+};
+pub use config::Config;
+pub use dataset::{DataSource, Dataset};
+pub use image_cache::ImageCache;
+pub use keymap::Keymap;
 pub use panes::Pane;
 pub use parse_csv::parse_csv;
+pub use schema::{ColumnType, Schema};
+pub use source::FileSource;
 pub use tab::Tab;
+pub use tui::{TuiOptions, run};
@@ -1,26 +1,315 @@
 #![warn(clippy::used_underscore_binding)]
+use std::io::Write as _;
+
 use clap::Parser as _;
 use color_eyre::Result;
-
-mod app;
-mod ui;
-
-use app::App;
+use color_eyre::eyre::OptionExt;
+use pixmux::dataset::LoadOptions;
+use pixmux::parse_csv::CsvOptions;
+use pixmux::parse_sqlite::SqliteOptions;
+use pixmux::{ColumnType, Config, FileSource, TuiOptions};
+use ratatui_image::picker::ProtocolType;
 
 #[derive(clap::Parser)]
-#[command(about = "A tui-app for viewing images associated with tabular data")]
+#[command(
+    about = "A tui-app for viewing images associated with tabular data",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct Args {
-    #[arg(help = "Path to .csv file", value_hint = clap::ValueHint::FilePath)]
-    pub file: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[arg(
+        required = true,
+        help = "Path to a .csv, .jsonl (optionally .gz or .zst compressed), .parquet or SQLite file, or - for stdin",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub file: Option<String>,
+
+    #[command(flatten)]
+    pub source: SourceArgs,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Force a graphics protocol instead of querying the terminal"
+    )]
+    pub protocol: Option<ProtocolArg>,
+
+    #[arg(
+        long,
+        value_name = "WxH",
+        value_parser = parse_font_size,
+        help = "Terminal cell size in pixels, skips querying the terminal (e.g. 8x16)"
+    )]
+    pub font_size: Option<(u16, u16)>,
+
+    #[arg(
+        long,
+        default_value_t = 2,
+        help = "Number of rows before and after the current one whose images are decoded ahead of time"
+    )]
+    pub prefetch_depth: usize,
+
+    #[arg(
+        long,
+        value_name = "MiB",
+        default_value_t = 256,
+        help = "Memory budget for decoded images"
+    )]
+    pub cache_memory: usize,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        value_hint = clap::ValueHint::FilePath,
+        help = "Config file to read instead of $XDG_CONFIG_HOME/pixmux/config.toml"
+    )]
+    pub config: Option<std::path::PathBuf>,
+
+    #[arg(
+        long,
+        help = "Skip rows with problems, such as a missing image directory, and list them instead of failing"
+    )]
+    pub lenient: bool,
+}
+
+#[derive(clap::Subcommand)]
+pub enum Command {
+    #[command(
+        about = "Check a dataset without opening the TUI, printing a JSON report and exiting with 1 if there are problems"
+    )]
+    Check {
+        #[arg(
+            help = "Path to a .csv, .jsonl (optionally .gz or .zst compressed), .parquet or SQLite file, or - for stdin",
+            value_hint = clap::ValueHint::FilePath
+        )]
+        file: std::path::PathBuf,
+
+        #[arg(long, help = "Indent the JSON report")]
+        pretty: bool,
+
+        #[command(flatten)]
+        source: SourceArgs,
+    },
+}
+
+#[derive(clap::Args)]
+pub struct SourceArgs {
+    #[arg(
+        long,
+        value_name = "CHAR",
+        value_parser = parse_ascii_char,
+        help = "Field delimiter, e.g. ';' or tab (sniffed from the file by default)"
+    )]
+    pub delimiter: Option<u8>,
+
+    #[arg(
+        long,
+        value_name = "CHAR",
+        value_parser = parse_ascii_char,
+        default_value = "\"",
+        help = "Quote character"
+    )]
+    pub quote: u8,
+
+    #[arg(
+        long,
+        value_name = "CHAR",
+        value_parser = parse_ascii_char,
+        help = "Skip lines starting with this character"
+    )]
+    pub comment: Option<u8>,
+
+    #[arg(
+        long,
+        help = "The file has no header row; columns are named col1, col2, … (needs --image-column)"
+    )]
+    pub no_header: bool,
+
+    #[arg(
+        long,
+        value_name = "COLUMN",
+        help = "Name or 1-based position of the column holding the image directories [default: _]"
+    )]
+    pub image_column: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "LABEL",
+        value_parser = parse_encoding,
+        default_value = "utf-8",
+        help = "Text encoding of the file, e.g. latin1 or windows-1252"
+    )]
+    pub encoding: &'static encoding_rs::Encoding,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        value_hint = clap::ValueHint::DirPath,
+        help = "Directory the image directories are relative to [default: the CSV file's directory, or the working directory for stdin]"
+    )]
+    pub root: Option<std::path::PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with = "query",
+        help = "Table or view of a SQLite database to show [default: its only table]"
+    )]
+    pub table: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "SQL",
+        help = "Query whose result to show from a SQLite database"
+    )]
+    pub query: Option<String>,
+
+    #[arg(
+        long = "type",
+        value_name = "COLUMN=TYPE",
+        value_parser = parse_column_type,
+        help = "Type of a column instead of the inferred one: integer, float, bool, timestamp or string (repeatable)"
+    )]
+    pub column_types: Vec<(String, ColumnType)>,
+}
+
+impl SourceArgs {
+    fn file_source(&self, file: impl Into<std::path::PathBuf>, lenient: bool) -> FileSource {
+        FileSource {
+            path: file.into(),
+            csv: CsvOptions {
+                delimiter: self.delimiter,
+                quote: self.quote,
+                comment: self.comment,
+                has_headers: !self.no_header,
+                encoding: self.encoding,
+            },
+            sqlite: SqliteOptions {
+                table: self.table.clone(),
+                query: self.query.clone(),
+            },
+            options: LoadOptions {
+                image_column: self.image_column.clone(),
+                root: self.root.clone(),
+                column_types: self.column_types.iter().cloned().collect(),
+                lenient,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum ProtocolArg {
+    Sixel,
+    Kitty,
+    Iterm2,
+    Halfblocks,
+}
+
+impl From<ProtocolArg> for ProtocolType {
+    fn from(value: ProtocolArg) -> Self {
+        match value {
+            ProtocolArg::Sixel => ProtocolType::Sixel,
+            ProtocolArg::Kitty => ProtocolType::Kitty,
+            ProtocolArg::Iterm2 => ProtocolType::Iterm2,
+            ProtocolArg::Halfblocks => ProtocolType::Halfblocks,
+        }
+    }
+}
+
+fn parse_font_size(value: &str) -> Result<(u16, u16), String> {
+    let (width, height) = value
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("expected WxH, got \"{value}\""))?;
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<u16>()
+            .ok()
+            .filter(|&v| v > 0)
+            .ok_or_else(|| format!("invalid pixel size \"{v}\""))
+    };
+    Ok((parse(width)?, parse(height)?))
+}
+
+fn check(file: &std::path::PathBuf, pretty: bool, source: &SourceArgs) -> Result<()> {
+    let report = pixmux::check::check(&mut source.file_source(file, true))?;
+    // Written rather than printed, so that a closed pipe is an error instead of a panic
+    let mut stdout = std::io::stdout().lock();
+    if pretty {
+        serde_json::to_writer_pretty(&mut stdout, &report)?;
+    } else {
+        serde_json::to_writer(&mut stdout, &report)?;
+    }
+    writeln!(stdout)?;
+    if !report.is_ok() {
+        eprintln!(
+            "{} problems in {} rows of {}",
+            report.problems.len(),
+            report.rows,
+            file.display()
+        );
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// A single ASCII character, or `tab` for a tab
+fn parse_ascii_char(value: &str) -> Result<u8, String> {
+    match value {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!(
+            "expected a single ASCII character, got \"{value}\""
+        )),
+    }
+}
+
+fn parse_column_type(value: &str) -> Result<(String, ColumnType), String> {
+    let (column, column_type) = value
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected COLUMN=TYPE, got \"{value}\""))?;
+    let column_type = column_type.parse().map_err(|error| format!("{error}"))?;
+    Ok((column.to_string(), column_type))
+}
+
+fn parse_encoding(value: &str) -> Result<&'static encoding_rs::Encoding, String> {
+    encoding_rs::Encoding::for_label(value.as_bytes())
+        .ok_or_else(|| format!("unknown encoding \"{value}\""))
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
     let args = Args::parse();
+    if let Some(Command::Check {
+        file,
+        pretty,
+        source,
+    }) = &args.command
+    {
+        return check(file, *pretty, source);
+    }
+    let mut config = match &args.config {
+        Some(path) => Config::load(path)?,
+        None => Config::load_default()?,
+    };
+
+    let file = args.file.as_deref().ok_or_eyre("Missing CSV file")?;
+    // Types given on the command line win over the config file
+    config
+        .column_types
+        .extend(args.source.column_types.iter().cloned());
+    let mut source = args.source.file_source(file, args.lenient);
+    source.options.column_types = config.column_types.clone();
+    let options = TuiOptions {
+        protocol: args.protocol.map(ProtocolType::from),
+        font_size: args.font_size,
+        prefetch_depth: args.prefetch_depth,
+        cache_memory: args.cache_memory.saturating_mul(1024 * 1024),
+    };
 
-    let terminal = ratatui::init();
-    let result = App::new(args.file.into())?.run(terminal);
-    ratatui::restore();
-    result
+    pixmux::run(Box::new(source), config, &options)
 }
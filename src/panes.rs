@@ -199,6 +199,7 @@ impl Pane {
 
         match node {
             Pane::Split { .. } => Err(eyre::eyre!("Cannot change image on a split node")),
+            Pane::Leaf { .. } if size == 0 => Err(eyre::eyre!("There are no images to show")),
             Pane::Leaf { image_id } => {
                 *image_id = crate::cycle_index(*image_id, size, direction);
                 Ok(())
@@ -206,6 +207,18 @@ impl Pane {
         }
     }
 
+    pub fn set_image(&mut self, path: &[bool], image_id: usize) -> eyre::Result<()> {
+        let node = self.get_node_at_mut(path)?;
+
+        match node {
+            Pane::Split { .. } => Err(eyre::eyre!("Cannot change image on a split node")),
+            Pane::Leaf { image_id: current } => {
+                *current = image_id;
+                Ok(())
+            }
+        }
+    }
+
     pub fn navigate(
         &self,
         path: &[bool],
@@ -480,7 +493,7 @@ mod tests {
             };
             let promoted_sibling_path = tree.remove_leaf_at(&[remove_child]).unwrap();
 
-            assert_eq!(promoted_sibling_path, vec![]);
+            assert_eq!(promoted_sibling_path, Vec::<bool>::new());
             assert!(matches!(tree, Pane::Leaf { image_id: e } if e==expected));
         }
     }
@@ -506,7 +519,7 @@ mod tests {
         let mut tree = Pane::Leaf { image_id: 1 };
         let promoted_sibling_path = tree.remove_leaf_at(&[]).unwrap();
 
-        assert_eq!(promoted_sibling_path, vec![]);
+        assert_eq!(promoted_sibling_path, Vec::<bool>::new());
         assert!(matches!(tree, Pane::Leaf { image_id: 0 }));
     }
 
@@ -561,10 +574,10 @@ mod tests {
         let tree = Pane::new_leaf();
 
         let next = tree.cycle(&[], AdjustDirection::Next).unwrap();
-        assert_eq!(next, vec![]);
+        assert_eq!(next, Vec::<bool>::new());
 
         let prev = tree.cycle(&[], AdjustDirection::Previous).unwrap();
-        assert_eq!(prev, vec![]);
+        assert_eq!(prev, Vec::<bool>::new());
     }
 
     #[test]
@@ -786,6 +799,25 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_err_cycle_image_without_images() {
+        let mut tree = Pane::new_leaf();
+
+        assert!(tree.cycle_image(&[], 0, AdjustDirection::Next).is_err());
+    }
+
+    #[test]
+    fn test_set_image_id() {
+        let mut tree = Pane::new_split(layout::Direction::Horizontal);
+
+        tree.set_image(&[false], 2).unwrap();
+        assert!(matches!(
+            tree.get_node_at(&[false]).unwrap(),
+            Pane::Leaf { image_id: 2 }
+        ));
+        assert!(tree.set_image(&[], 1).is_err());
+    }
+
     // Navigate directions
     #[test]
     fn test_root_leaf_on_navigate_does_nothing() {
@@ -794,17 +826,17 @@ mod tests {
         let nextv = tree
             .navigate(&[], layout::Direction::Vertical, AdjustDirection::Next)
             .unwrap();
-        assert_eq!(nextv, vec![]);
+        assert_eq!(nextv, Vec::<bool>::new());
 
         let prevv = tree
             .navigate(&[], layout::Direction::Vertical, AdjustDirection::Previous)
             .unwrap();
-        assert_eq!(prevv, vec![]);
+        assert_eq!(prevv, Vec::<bool>::new());
 
         let nexth = tree
             .navigate(&[], layout::Direction::Horizontal, AdjustDirection::Next)
             .unwrap();
-        assert_eq!(nexth, vec![]);
+        assert_eq!(nexth, Vec::<bool>::new());
 
         let prevh = tree
             .navigate(
@@ -813,7 +845,7 @@ mod tests {
                 AdjustDirection::Previous,
             )
             .unwrap();
-        assert_eq!(prevh, vec![]);
+        assert_eq!(prevh, Vec::<bool>::new());
     }
 
     #[test]
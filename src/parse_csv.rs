@@ -1,64 +1,167 @@
+use std::collections::HashMap;
 use std::path;
 
-use color_eyre::Result;
-use color_eyre::eyre::{self, OptionExt};
+use color_eyre::{Result, eyre};
 
-type CsvData = (Vec<String>, Vec<Vec<String>>, Vec<path::PathBuf>);
+use crate::dataset::{DataSource, Dataset, LoadOptions, Records, Table};
+use crate::source::{decode_text, is_stdin, read_input};
 
-pub fn parse_csv(filepath: &path::PathBuf) -> Result<CsvData> {
-    let mut rdr = csv::Reader::from_path(filepath)?;
-
-    let headers: Vec<String> = rdr.headers()?.iter().map(String::from).collect();
-
-    let rows: Vec<Vec<String>> = rdr
-        .records()
-        .collect::<Result<Vec<_>, _>>()?
-        .into_iter()
-        .map(|record| record.into_iter().map(String::from).collect())
-        .collect();
-
-    let underscore_index = headers
-        .iter()
-        .position(|h| h == "_")
-        .ok_or_eyre("Missing \"_\" column")?;
-
-    let headers: Vec<String> = headers
-        .into_iter()
-        .enumerate()
-        .filter_map(|(i, h)| (i != underscore_index).then_some(h))
-        .collect();
-    if headers.is_empty() {
-        return Err(eyre::eyre!("Missing data columns"));
-    }
-
-    let csv_dir = filepath
-        .parent()
-        .ok_or_eyre("Could not determine parent directory of CSV file")?;
-
-    let row_dirs: Vec<path::PathBuf> = rows
-        .iter()
-        .map(|row| csv_dir.join(&row[underscore_index]))
-        .collect();
-
-    for dir in &row_dirs {
-        if !dir.is_dir() {
-            return Err(eyre::eyre!(
-                "The \"_\" column in the CSV must correspond to dirs!"
-            ));
+pub type CsvData = (Vec<String>, Vec<Vec<String>>, Vec<path::PathBuf>);
+
+// Tried in order when sniffing, so commas win whenever they fit
+const SNIFFED_DELIMITERS: [u8; 4] = *b",\t;|";
+const SNIFFED_RECORDS: usize = 20;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    // Sniffed from the start of the file when not set
+    pub delimiter: Option<u8>,
+    pub quote: u8,
+    // Lines starting with this byte are skipped
+    pub comment: Option<u8>,
+    // Without a header row, columns are named `col1`, `col2`, …
+    pub has_headers: bool,
+    pub encoding: &'static encoding_rs::Encoding,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            quote: b'"',
+            comment: None,
+            has_headers: true,
+            encoding: encoding_rs::UTF_8,
         }
     }
+}
 
-    let rows: Vec<Vec<String>> = rows
-        .into_iter()
-        .map(|row| {
-            row.into_iter()
-                .enumerate()
-                .filter_map(|(i, val)| (i != underscore_index).then_some(val))
-                .collect()
-        })
-        .collect();
+// Picks the candidate that splits the most of the first records into the same number (more than one) of fields;
+// earlier candidates win ties
+pub fn sniff_delimiter(text: &str, quote: u8, comment: Option<u8>) -> u8 {
+    let mut best = (b',', 0);
+    for delimiter in SNIFFED_DELIMITERS {
+        let records = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(delimiter)
+            .quote(quote)
+            .comment(comment)
+            .from_reader(text.as_bytes())
+            .into_records();
+        let mut field_counts: HashMap<usize, usize> = HashMap::new();
+        for record in records.take(SNIFFED_RECORDS).map_while(Result::ok) {
+            *field_counts.entry(record.len()).or_default() += 1;
+        }
+        let consistent = field_counts
+            .into_iter()
+            .filter(|&(fields, _)| fields > 1)
+            .map(|(_, records)| records)
+            .max()
+            .unwrap_or(0);
+        if consistent > best.1 {
+            best = (delimiter, consistent);
+        }
+    }
+    best.0
+}
 
-    Ok((headers, rows, row_dirs))
+fn read_csv_records(text: &str, options: &CsvOptions) -> Result<Records> {
+    let delimiter = options
+        .delimiter
+        .unwrap_or_else(|| sniff_delimiter(text, options.quote, options.comment));
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .flexible(true)
+        .delimiter(delimiter)
+        .quote(options.quote)
+        .comment(options.comment)
+        .has_headers(options.has_headers)
+        .from_reader(text.as_bytes());
+
+    let headers: Vec<String> = if options.has_headers {
+        rdr.headers()?.iter().map(String::from).collect()
+    } else {
+        (1..=rdr.headers()?.len())
+            .map(|position| format!("col{position}"))
+            .collect()
+    };
+
+    let mut records = Vec::new();
+    for record in rdr.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |position| position.line());
+        records.push((line, record.iter().map(String::from).collect()));
+    }
+    Ok(Records {
+        headers,
+        rows: records,
+        issues: vec![],
+        schema: None,
+    })
+}
+
+pub(crate) fn read_csv_table(
+    filepath: &path::Path,
+    bytes: &[u8],
+    csv: &CsvOptions,
+    options: &LoadOptions,
+) -> Result<Table> {
+    if !csv.has_headers && options.image_column.is_none() {
+        return Err(eyre::eyre!(
+            "A file without a header row needs an image column"
+        ));
+    }
+    let text = decode_text(filepath, bytes, csv.encoding)?;
+    let records = read_csv_records(&text, csv)?;
+    Table::from_records(records, &options.base_dir(filepath)?, options)
+}
+
+// A delimited text file, optionally gzip or zstd compressed
+#[derive(Clone, Debug)]
+pub struct CsvSource {
+    pub path: path::PathBuf,
+    pub csv: CsvOptions,
+    pub options: LoadOptions,
+}
+
+impl CsvSource {
+    pub fn new(path: impl Into<path::PathBuf>, csv: CsvOptions, options: LoadOptions) -> Self {
+        CsvSource {
+            path: path.into(),
+            csv,
+            options,
+        }
+    }
+
+    pub fn read(&self) -> Result<Table> {
+        read_csv_table(
+            &self.path,
+            &read_input(&self.path)?,
+            &self.csv,
+            &self.options,
+        )
+    }
+}
+
+impl DataSource for CsvSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&mut self) -> Result<Box<dyn Dataset>> {
+        Ok(Box::new(self.read()?))
+    }
+
+    fn can_reload(&self) -> bool {
+        !is_stdin(&self.path)
+    }
+}
+
+// Fails on the first row with a problem
+pub fn parse_csv(filepath: &path::PathBuf) -> Result<CsvData> {
+    let table = CsvSource::new(filepath, CsvOptions::default(), LoadOptions::default()).read()?;
+    Ok((table.headers, table.rows, table.image_dirs))
 }
 
 #[cfg(test)]
@@ -66,6 +169,8 @@ mod tests {
     use std::io::Write;
 
     use super::*;
+    use crate::dataset::{RowIssue, RowProblem};
+    use crate::schema::{ColumnType, TypeOverrides};
 
     #[test]
     fn test_ok_onerow_onedatacolumn() {
@@ -182,4 +287,237 @@ value,not_a_dir";
 
         assert!(result.is_err());
     }
+
+    fn write_csv(content: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    fn lenient() -> LoadOptions {
+        LoadOptions {
+            lenient: true,
+            ..LoadOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_lenient_skips_and_reports_rows() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let created_dir = tmpdir.path().file_name().unwrap().to_str().unwrap();
+        let file = write_csv(&format!(
+            "\
+datacol,_
+good,{created_dir}
+short
+empty,
+missing,not_a_dir
+\"two
+lines\",{created_dir}"
+        ));
+
+        let Table {
+            headers,
+            rows,
+            image_dirs: row_dirs,
+            lines,
+            issues,
+            schema,
+        } = CsvSource::new(file.path(), CsvOptions::default(), lenient())
+            .read()
+            .unwrap();
+
+        assert_eq!(headers, vec!["datacol"]);
+        assert_eq!(schema.types, vec![ColumnType::String]);
+        assert_eq!(rows, vec![vec!["good"], vec!["two\nlines"]]);
+        assert_eq!(row_dirs.len(), 2);
+        assert_eq!(lines, vec![2, 6]);
+        let csv_dir = file.path().parent().unwrap();
+        assert_eq!(
+            issues,
+            vec![
+                RowIssue {
+                    line: 3,
+                    problem: RowProblem::WrongColumnCount {
+                        expected: 2,
+                        found: 1
+                    }
+                },
+                RowIssue {
+                    line: 4,
+                    problem: RowProblem::EmptyDir
+                },
+                RowIssue {
+                    line: 5,
+                    problem: RowProblem::MissingDir(csv_dir.join("not_a_dir"))
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_of_loaded_rows() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let created_dir = tmpdir.path().file_name().unwrap().to_str().unwrap();
+        let file = write_csv(&format!(
+            "epoch,loss,run,_\n1,0.5,7,{created_dir}\nbad,x,y,not_a_dir\n12,,9,{created_dir}\n"
+        ));
+        let options = LoadOptions {
+            column_types: TypeOverrides::from([("run".to_string(), ColumnType::String)]),
+            ..lenient()
+        };
+
+        let table = CsvSource::new(file.path(), CsvOptions::default(), options)
+            .read()
+            .unwrap();
+
+        // Skipped rows and the image column don't count
+        assert_eq!(
+            table.schema.types,
+            vec![ColumnType::Integer, ColumnType::Float, ColumnType::String]
+        );
+    }
+
+    #[test]
+    fn test_strict_reports_first_problem_line() {
+        let file = write_csv("datacol,_\nvalue,\nvalue,not_a_dir");
+
+        let err = parse_csv(&file.path().to_path_buf()).unwrap_err();
+
+        // An empty `_` is the CSV's own directory
+        assert!(err.to_string().starts_with("line 3: not a directory: "));
+    }
+
+    #[test]
+    fn test_strict_empty_dir_is_csv_dir() {
+        let file = write_csv(
+            "datacol,_
+value,
+",
+        );
+
+        let (_, _, image_dirs) = parse_csv(&file.path().to_path_buf()).unwrap();
+
+        assert_eq!(
+            image_dirs,
+            vec![file.path().parent().unwrap().to_path_buf()]
+        );
+    }
+
+    #[test]
+    fn test_sniff_delimiter() {
+        let test_cases: [(&str, u8); 8] = [
+            ("a,b,_\n1,2,x\n", b','),
+            ("a\tb\t_\n1\t2\tx\n", b'\t'),
+            ("a;b;_\n1,5;2,5;x\n", b';'),
+            ("a|_\n1|x\n", b'|'),
+            ("a;_\n\"1,5\";x\n\"2,25\";y\n", b';'),
+            ("single\nvalue\n", b','),
+            // A short row doesn't rule the delimiter out
+            ("a;b;_\n1,5;2;x\n3\n4;5,5;y\n", b';'),
+            ("a;b;_\n\"two\nlines, one; two\";2;x\n3;4;y\n", b';'),
+        ];
+
+        for (text, expected) in test_cases {
+            assert_eq!(sniff_delimiter(text, b'"', None), expected, "{text}");
+        }
+        assert_eq!(
+            sniff_delimiter("# a,b,c\na;_\n1;x\n", b'"', Some(b'#')),
+            b';'
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let created_dir = tmpdir.path().file_name().unwrap().to_str().unwrap();
+        // "Müller" in Latin-1, semicolon separated, with a comment and no header row
+        let mut content = b"# exported\n'M\xfcller';".to_vec();
+        content.extend_from_slice(created_dir.as_bytes());
+        content.extend_from_slice(b";'1;2'\n");
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&content).unwrap();
+        let options = CsvOptions {
+            quote: b'\'',
+            comment: Some(b'#'),
+            has_headers: false,
+            encoding: encoding_rs::WINDOWS_1252,
+            ..CsvOptions::default()
+        };
+        let image_column = |image_column: Option<&str>| LoadOptions {
+            image_column: image_column.map(String::from),
+            ..LoadOptions::default()
+        };
+
+        let table = CsvSource::new(file.path(), options.clone(), image_column(Some("2")))
+            .read()
+            .unwrap();
+
+        assert_eq!(table.headers, vec!["col1", "col3"]);
+        assert_eq!(table.rows, vec![vec!["Müller", "1;2"]]);
+        assert_eq!(table.image_dirs, vec![tmpdir.path().to_path_buf()]);
+        assert!(
+            CsvSource::new(file.path(), options, image_column(None))
+                .read()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_image_column_by_name() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let created_dir = tmpdir.path().file_name().unwrap().to_str().unwrap();
+        let file = write_csv(&format!("name\tdir\nvalue\t{created_dir}"));
+        let read = |image_column: &str| {
+            let options = LoadOptions {
+                image_column: Some(image_column.to_string()),
+                ..LoadOptions::default()
+            };
+            CsvSource::new(file.path(), CsvOptions::default(), options).read()
+        };
+
+        let table = read("dir").unwrap();
+
+        assert_eq!(table.headers, vec!["name"]);
+        assert_eq!(table.image_dirs, vec![tmpdir.path().to_path_buf()]);
+        assert!(read("nope").is_err());
+    }
+
+    #[test]
+    fn test_err_invalid_utf8() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"name,_\nM\xfcller,x\n").unwrap();
+
+        assert!(parse_csv(&file.path().to_path_buf()).is_err());
+    }
+
+    #[test]
+    fn test_compressed_input_and_root() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let created_dir = tmpdir.path().file_name().unwrap().to_str().unwrap();
+        let content = format!("name,_\nvalue,{created_dir}\n");
+        let options = LoadOptions {
+            root: tmpdir.path().parent().map(path::Path::to_path_buf),
+            ..LoadOptions::default()
+        };
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(content.as_bytes()).unwrap();
+        let compressed = [
+            gzip.finish().unwrap(),
+            zstd::encode_all(content.as_bytes(), 0).unwrap(),
+        ];
+        for bytes in compressed {
+            let mut file = tempfile::NamedTempFile::new_in(tmpdir.path()).unwrap();
+            file.write_all(&bytes).unwrap();
+
+            let table = CsvSource::new(file.path(), CsvOptions::default(), options.clone())
+                .read()
+                .unwrap();
+
+            assert_eq!(table.headers, vec!["name"]);
+            assert_eq!(table.rows, vec![vec!["value"]]);
+            assert_eq!(table.image_dirs, vec![tmpdir.path().to_path_buf()]);
+        }
+    }
 }
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::path;
+
+use color_eyre::{Result, eyre};
+use serde_json::Value;
+
+use crate::dataset::{DataSource, Dataset, LoadOptions, Records, RowIssue, RowProblem, Table};
+use crate::source::{decode_text, is_stdin, read_input};
+
+// Nested objects become dotted names such as `metrics.loss`; arrays are kept as JSON text
+fn flatten(prefix: &str, value: Value, fields: &mut Vec<(String, String)>) {
+    let text = match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let name = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&name, value, fields);
+            }
+            return;
+        }
+        Value::Null => String::new(),
+        Value::String(text) => text,
+        other => other.to_string(),
+    };
+    fields.push((prefix.to_string(), text));
+}
+
+// Columns are every name seen in any object, in the order they first appear; objects without one have an empty cell.
+// Lines that aren't JSON objects are issues, unless no line is one.
+pub(crate) fn read_jsonl_records(text: &str) -> Result<Records> {
+    let mut headers: Vec<String> = Vec::new();
+    let mut columns: HashMap<String, usize> = HashMap::new();
+    let mut objects: Vec<(u64, Vec<(String, String)>)> = Vec::new();
+    let mut issues: Vec<RowIssue> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = index as u64 + 1;
+        let error = match serde_json::from_str::<Value>(line) {
+            Ok(value) if value.is_object() => {
+                let mut fields = Vec::new();
+                flatten("", value, &mut fields);
+                for (name, _) in &fields {
+                    if !columns.contains_key(name) {
+                        columns.insert(name.clone(), headers.len());
+                        headers.push(name.clone());
+                    }
+                }
+                objects.push((line_number, fields));
+                continue;
+            }
+            Ok(_) => "expected an object".to_string(),
+            // serde_json counts lines within the line it was given
+            Err(error) => match error.to_string().rsplit_once(" at line ") {
+                Some((message, _)) => format!("{message} at column {}", error.column()),
+                None => error.to_string(),
+            },
+        };
+        issues.push(RowIssue {
+            line: line_number,
+            problem: RowProblem::InvalidJson(error),
+        });
+    }
+    if let (true, Some(issue)) = (objects.is_empty(), issues.first()) {
+        return Err(eyre::eyre!("{issue}"));
+    }
+
+    let records = objects
+        .into_iter()
+        .map(|(line, fields)| {
+            let mut values = vec![String::new(); headers.len()];
+            for (name, value) in fields {
+                values[columns[&name]] = value;
+            }
+            (line, values)
+        })
+        .collect();
+    Ok(Records {
+        headers,
+        rows: records,
+        issues,
+        schema: None,
+    })
+}
+
+pub(crate) fn read_jsonl_table(
+    filepath: &path::Path,
+    bytes: &[u8],
+    options: &LoadOptions,
+) -> Result<Table> {
+    let text = decode_text(filepath, bytes, encoding_rs::UTF_8)?;
+    let records = read_jsonl_records(&text)?;
+    Table::from_records(records, &options.base_dir(filepath)?, options)
+}
+
+// One JSON object per line, optionally gzip or zstd compressed
+#[derive(Clone, Debug)]
+pub struct JsonlSource {
+    pub path: path::PathBuf,
+    pub options: LoadOptions,
+}
+
+impl JsonlSource {
+    pub fn new(path: impl Into<path::PathBuf>, options: LoadOptions) -> Self {
+        JsonlSource {
+            path: path.into(),
+            options,
+        }
+    }
+
+    pub fn read(&self) -> Result<Table> {
+        read_jsonl_table(&self.path, &read_input(&self.path)?, &self.options)
+    }
+}
+
+impl DataSource for JsonlSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&mut self) -> Result<Box<dyn Dataset>> {
+        Ok(Box::new(self.read()?))
+    }
+
+    fn can_reload(&self) -> bool {
+        !is_stdin(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_read_jsonl_records() {
+        let text = r#"{"model": "unet", "metrics": {"loss": 0.15, "ok": true}, "_": "r1"}
+
+{"_": "r2", "model": "vit", "tags": ["a", "b"], "metrics": {"loss": null}}
+"#;
+
+        let Records {
+            headers,
+            rows: records,
+            issues,
+            schema,
+        } = read_jsonl_records(text).unwrap();
+
+        assert_eq!(
+            headers,
+            vec!["model", "metrics.loss", "metrics.ok", "_", "tags"]
+        );
+        assert_eq!(
+            records,
+            vec![
+                (1, vec!["unet", "0.15", "true", "r1", ""]),
+                (3, vec!["vit", "", "", "r2", r#"["a","b"]"#]),
+            ]
+            .into_iter()
+            .map(|(line, values)| (line, values.into_iter().map(String::from).collect()))
+            .collect::<Vec<(u64, Vec<String>)>>()
+        );
+        assert!(issues.is_empty());
+        assert_eq!(schema, None);
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        let records = read_jsonl_records("{\"a\": 1}\n{\"a\": \n[1, 2]\n").unwrap();
+
+        assert_eq!(records.rows.len(), 1);
+        assert_eq!(
+            records.issues,
+            vec![
+                RowIssue {
+                    line: 2,
+                    problem: RowProblem::InvalidJson(
+                        "EOF while parsing a value at column 6".to_string()
+                    )
+                },
+                RowIssue {
+                    line: 3,
+                    problem: RowProblem::InvalidJson("expected an object".to_string())
+                },
+            ]
+        );
+
+        let err = read_jsonl_records("\n[1, 2]\nnope\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: invalid JSON: expected an object");
+    }
+
+    #[test]
+    fn test_parse_jsonl_file() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let created_dir = tmpdir.path().file_name().unwrap().to_str().unwrap();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"run": {{"dir": "{created_dir}"}}, "loss": 1}}"#).unwrap();
+        writeln!(file, r#"{{"loss": 2}}"#).unwrap();
+        let options = LoadOptions {
+            image_column: Some("run.dir".to_string()),
+            lenient: true,
+            ..LoadOptions::default()
+        };
+
+        let table = JsonlSource::new(file.path(), options.clone())
+            .read()
+            .unwrap();
+
+        assert_eq!(table.headers, vec!["loss"]);
+        assert_eq!(table.rows, vec![vec!["1"]]);
+        assert_eq!(table.image_dirs, vec![tmpdir.path().to_path_buf()]);
+        assert_eq!(table.issues.len(), 1);
+        assert_eq!(table.issues[0].line, 2);
+
+        writeln!(file, "not json").unwrap();
+        let mut source = JsonlSource::new(file.path(), options);
+        assert_eq!(source.read().unwrap().issues.len(), 2);
+        source.options.lenient = false;
+        assert!(source.read().is_err());
+    }
+}
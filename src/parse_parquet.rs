@@ -0,0 +1,250 @@
+use std::path;
+
+use color_eyre::Result;
+use parquet::basic::{ConvertedType, LogicalType, Repetition, Type as PhysicalType};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use parquet::schema::types::Type;
+
+use crate::dataset::{DataSource, Dataset, LoadOptions, Records, Table};
+use crate::schema::{ColumnType, Schema};
+use crate::source::{is_stdin, read_input};
+
+pub const PARQUET_MAGIC: &[u8] = b"PAR1";
+
+// Values are shown the way their type prints them, except that strings aren't quoted and nulls are empty
+fn format_field(field: &Field) -> String {
+    match field {
+        Field::Null => String::new(),
+        Field::Str(value) => value.clone(),
+        Field::Bytes(value) => value
+            .as_utf8()
+            .map_or_else(|_| field.to_string(), String::from),
+        // Parquet prints negative numbers and zero in scientific notation
+        Field::Float(value) => value.to_string(),
+        Field::Double(value) => value.to_string(),
+        _ => field.to_string(),
+    }
+}
+
+// The type of the values as `format_field` prints them; lists and groups are text
+fn column_type(field: &Type) -> ColumnType {
+    let info = field.get_basic_info();
+    if !field.is_primitive() || (info.has_repetition() && info.repetition() == Repetition::REPEATED)
+    {
+        return ColumnType::String;
+    }
+    match (info.logical_type(), info.converted_type()) {
+        (Some(LogicalType::Date | LogicalType::Timestamp { .. }), _)
+        | (
+            _,
+            ConvertedType::DATE | ConvertedType::TIMESTAMP_MILLIS | ConvertedType::TIMESTAMP_MICROS,
+        ) => return ColumnType::Timestamp,
+        (Some(LogicalType::Decimal { .. } | LogicalType::Float16), _)
+        | (_, ConvertedType::DECIMAL) => {
+            return ColumnType::Float;
+        }
+        (Some(LogicalType::Time { .. }), _)
+        | (_, ConvertedType::TIME_MILLIS | ConvertedType::TIME_MICROS) => {
+            return ColumnType::String;
+        }
+        _ => {}
+    }
+    match field.get_physical_type() {
+        PhysicalType::BOOLEAN => ColumnType::Bool,
+        PhysicalType::INT32 | PhysicalType::INT64 => ColumnType::Integer,
+        PhysicalType::INT96 => ColumnType::Timestamp,
+        PhysicalType::FLOAT | PhysicalType::DOUBLE => ColumnType::Float,
+        PhysicalType::BYTE_ARRAY | PhysicalType::FIXED_LEN_BYTE_ARRAY => ColumnType::String,
+    }
+}
+
+// Top-level columns only; nested groups and lists are shown as one cell
+pub(crate) fn read_parquet_records(bytes: Vec<u8>) -> Result<Records> {
+    let reader = SerializedFileReader::new(bytes::Bytes::from(bytes))?;
+    let fields = reader.metadata().file_metadata().schema().get_fields();
+    let headers: Vec<String> = fields
+        .iter()
+        .map(|field| field.name().to_string())
+        .collect();
+    let schema = Schema {
+        types: fields.iter().map(|field| column_type(field)).collect(),
+    };
+
+    let mut records = Vec::new();
+    for (index, row) in reader.get_row_iter(None)?.enumerate() {
+        let values = row?
+            .get_column_iter()
+            .map(|(_, field)| format_field(field))
+            .collect();
+        records.push((index as u64 + 1, values));
+    }
+    Ok(Records {
+        headers,
+        rows: records,
+        issues: vec![],
+        schema: Some(schema),
+    })
+}
+
+pub(crate) fn read_parquet_table(
+    filepath: &path::Path,
+    bytes: Vec<u8>,
+    options: &LoadOptions,
+) -> Result<Table> {
+    let records = read_parquet_records(bytes)?;
+    Table::from_records(records, &options.base_dir(filepath)?, options)
+}
+
+// Rows are numbered from 1 in file order
+#[derive(Clone, Debug)]
+pub struct ParquetSource {
+    pub path: path::PathBuf,
+    pub options: LoadOptions,
+}
+
+impl ParquetSource {
+    pub fn new(path: impl Into<path::PathBuf>, options: LoadOptions) -> Self {
+        ParquetSource {
+            path: path.into(),
+            options,
+        }
+    }
+
+    pub fn read(&self) -> Result<Table> {
+        read_parquet_table(&self.path, read_input(&self.path)?, &self.options)
+    }
+}
+
+impl DataSource for ParquetSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&mut self) -> Result<Box<dyn Dataset>> {
+        Ok(Box::new(self.read()?))
+    }
+
+    fn can_reload(&self) -> bool {
+        !is_stdin(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    use super::*;
+
+    fn write_parquet(dirs: &[&str]) -> Vec<u8> {
+        let schema = Arc::new(
+            parse_message_type(
+                "message schema {
+                    REQUIRED BINARY model (UTF8);
+                    REQUIRED INT64 epoch;
+                    OPTIONAL DOUBLE loss;
+                    REQUIRED BINARY _ (UTF8);
+                }",
+            )
+            .unwrap(),
+        );
+        let mut bytes = Vec::new();
+        let mut writer = SerializedFileWriter::new(&mut bytes, schema, Default::default()).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let strings = |values: &[&str]| -> Vec<ByteArray> {
+            values.iter().map(|&value| ByteArray::from(value)).collect()
+        };
+
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&strings(&["unet", "resnet"]), None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(&[12, 3], None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<DoubleType>()
+            .write_batch(&[-0.5], Some(&[1, 0]), None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&strings(dirs), None, None)
+            .unwrap();
+        column.close().unwrap();
+
+        row_group.close().unwrap();
+        writer.close().unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_read_parquet_records() {
+        let bytes = write_parquet(&["r1", "r2"]);
+        assert!(bytes.starts_with(PARQUET_MAGIC));
+
+        let Records {
+            headers,
+            rows: records,
+            schema,
+            ..
+        } = read_parquet_records(bytes).unwrap();
+
+        assert_eq!(headers, vec!["model", "epoch", "loss", "_"]);
+        assert_eq!(
+            schema.unwrap().types,
+            vec![
+                ColumnType::String,
+                ColumnType::Integer,
+                ColumnType::Float,
+                ColumnType::String
+            ]
+        );
+        assert_eq!(
+            records,
+            vec![
+                (
+                    1,
+                    vec!["unet".into(), "12".into(), "-0.5".into(), "r1".into()]
+                ),
+                (2, vec!["resnet".into(), "3".into(), "".into(), "r2".into()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_parquet_file() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let created_dir = tmpdir.path().file_name().unwrap().to_str().unwrap();
+        let file = tmpdir.path().join("index.parquet");
+        std::fs::write(&file, write_parquet(&[created_dir, "not_a_dir"])).unwrap();
+        let options = LoadOptions {
+            root: tmpdir.path().parent().map(path::Path::to_path_buf),
+            lenient: true,
+            ..LoadOptions::default()
+        };
+
+        let table = ParquetSource::new(&file, options).read().unwrap();
+
+        assert_eq!(table.headers, vec!["model", "epoch", "loss"]);
+        assert_eq!(
+            table.schema.types,
+            vec![ColumnType::String, ColumnType::Integer, ColumnType::Float]
+        );
+        assert_eq!(table.rows, vec![vec!["unet", "12", "-0.5"]]);
+        assert_eq!(table.image_dirs, vec![tmpdir.path().to_path_buf()]);
+        assert_eq!(table.issues.len(), 1);
+        assert_eq!(table.issues[0].line, 2);
+    }
+}
@@ -0,0 +1,336 @@
+use std::io::Read;
+use std::{fs, path};
+
+use color_eyre::Result;
+use color_eyre::eyre::{self, WrapErr};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+
+use crate::dataset::{DataSource, Dataset, LoadOptions, Records, Table};
+use crate::schema::{ColumnType, Schema};
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SqliteOptions {
+    // The table or view to show; a query wins if both are set
+    pub table: Option<String>,
+    pub query: Option<String>,
+}
+
+pub fn is_sqlite(filepath: &path::Path) -> bool {
+    let mut header = [0; SQLITE_MAGIC.len()];
+    fs::File::open(filepath)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|_| header == SQLITE_MAGIC)
+}
+
+fn format_value(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(value) => value.to_string(),
+        ValueRef::Real(value) => value.to_string(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned(),
+        ValueRef::Blob(blob) => format!("<{} bytes>", blob.len()),
+    }
+}
+
+fn table_names(connection: &Connection) -> Result<Vec<String>> {
+    let mut statement = connection.prepare(
+        "SELECT name FROM sqlite_schema WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )?;
+    let names = statement
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(names)
+}
+
+// Without a table or query, a database with a single table shows that one
+fn select_statement(connection: &Connection, options: &SqliteOptions) -> Result<String> {
+    if let Some(query) = &options.query {
+        return Ok(query.clone());
+    }
+    let table = match &options.table {
+        Some(table) => table.clone(),
+        None => match table_names(connection)?.as_slice() {
+            [table] => table.clone(),
+            [] => return Err(eyre::eyre!("The database has no tables")),
+            tables => {
+                return Err(eyre::eyre!(
+                    "The database has several tables, pick one of: {}",
+                    tables.join(", ")
+                ));
+            }
+        },
+    };
+    Ok(format!("SELECT * FROM \"{}\"", table.replace('"', "\"\"")))
+}
+
+// SQLite's type affinity rules, with dates and times told apart from other numeric columns
+fn declared_type(declared: &str) -> ColumnType {
+    let declared = declared.to_ascii_uppercase();
+    let has = |part: &str| declared.contains(part);
+    if has("INT") {
+        ColumnType::Integer
+    } else if has("CHAR") || has("CLOB") || has("TEXT") || has("BLOB") {
+        ColumnType::String
+    } else if has("DATE") || has("TIME") {
+        ColumnType::Timestamp
+    } else {
+        ColumnType::Float
+    }
+}
+
+// For columns without a declared type, such as expressions in a query: the storage class of their values
+fn stored_type(seen: Option<ColumnType>, value: ValueRef) -> Option<ColumnType> {
+    match (seen, value) {
+        (seen, ValueRef::Null) => seen,
+        (None | Some(ColumnType::Integer), ValueRef::Integer(_)) => Some(ColumnType::Integer),
+        (
+            None | Some(ColumnType::Integer | ColumnType::Float),
+            ValueRef::Integer(_) | ValueRef::Real(_),
+        ) => Some(ColumnType::Float),
+        _ => Some(ColumnType::String),
+    }
+}
+
+// Rows are numbered from 1 in the order the query returns them
+pub(crate) fn read_sqlite_records(
+    filepath: &path::Path,
+    options: &SqliteOptions,
+) -> Result<Records> {
+    let connection = Connection::open_with_flags(filepath, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .wrap_err_with(|| format!("Could not open {}", filepath.display()))?;
+    let sql = select_statement(&connection, options)?;
+    let mut statement = connection
+        .prepare(&sql)
+        .wrap_err_with(|| format!("Invalid query \"{sql}\""))?;
+    let headers: Vec<String> = statement
+        .column_names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let declared: Vec<Option<ColumnType>> = statement
+        .columns()
+        .iter()
+        .map(|column| {
+            column
+                .decl_type()
+                .filter(|declared| !declared.is_empty())
+                .map(declared_type)
+        })
+        .collect();
+
+    let mut stored: Vec<Option<ColumnType>> = vec![None; headers.len()];
+    let mut records = Vec::new();
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        let mut values = Vec::with_capacity(headers.len());
+        for (column, seen) in stored.iter_mut().enumerate() {
+            let value = row.get_ref(column)?;
+            *seen = stored_type(*seen, value);
+            values.push(format_value(value));
+        }
+        records.push((records.len() as u64 + 1, values));
+    }
+    let types = declared
+        .into_iter()
+        .zip(stored)
+        .map(|(declared, stored)| declared.or(stored).unwrap_or(ColumnType::String))
+        .collect();
+    Ok(Records {
+        headers,
+        rows: records,
+        issues: vec![],
+        schema: Some(Schema { types }),
+    })
+}
+
+// Image directories are relative to the database's directory
+pub(crate) fn read_sqlite_table(
+    filepath: &path::Path,
+    sqlite: &SqliteOptions,
+    options: &LoadOptions,
+) -> Result<Table> {
+    let records = read_sqlite_records(filepath, sqlite)?;
+    Table::from_records(records, &options.base_dir(filepath)?, options)
+}
+
+#[derive(Clone, Debug)]
+pub struct SqliteSource {
+    pub path: path::PathBuf,
+    pub sqlite: SqliteOptions,
+    pub options: LoadOptions,
+}
+
+impl SqliteSource {
+    pub fn new(
+        path: impl Into<path::PathBuf>,
+        sqlite: SqliteOptions,
+        options: LoadOptions,
+    ) -> Self {
+        SqliteSource {
+            path: path.into(),
+            sqlite,
+            options,
+        }
+    }
+
+    pub fn read(&self) -> Result<Table> {
+        read_sqlite_table(&self.path, &self.sqlite, &self.options)
+    }
+}
+
+impl DataSource for SqliteSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&mut self) -> Result<Box<dyn Dataset>> {
+        Ok(Box::new(self.read()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_database(path: &path::Path) {
+        let connection = Connection::open(path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE evals (model TEXT, epoch INTEGER, loss REAL, _ TEXT);
+                 INSERT INTO evals VALUES ('unet', 12, 0.15, 'r1'), ('vit', 3, NULL, 'r2');",
+            )
+            .unwrap();
+    }
+
+    fn owned(records: &[(u64, &[&str])]) -> Vec<(u64, Vec<String>)> {
+        records
+            .iter()
+            .map(|(line, values)| (*line, values.iter().map(|v| v.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_read_table_and_query() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let path = tmpdir.path().join("results.db");
+        create_database(&path);
+        assert!(is_sqlite(&path));
+
+        let Records {
+            headers,
+            rows: records,
+            ..
+        } = read_sqlite_records(&path, &SqliteOptions::default()).unwrap();
+        assert_eq!(headers, vec!["model", "epoch", "loss", "_"]);
+        assert_eq!(
+            records,
+            owned(&[
+                (1, &["unet", "12", "0.15", "r1"]),
+                (2, &["vit", "3", "", "r2"])
+            ])
+        );
+
+        let options = SqliteOptions {
+            query: Some("SELECT model, _ AS dir FROM evals WHERE epoch > 5".to_string()),
+            ..SqliteOptions::default()
+        };
+        let Records {
+            headers,
+            rows: records,
+            ..
+        } = read_sqlite_records(&path, &options).unwrap();
+        assert_eq!(headers, vec!["model", "dir"]);
+        assert_eq!(records, owned(&[(1, &["unet", "r1"])]));
+    }
+
+    #[test]
+    fn test_column_types() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let path = tmpdir.path().join("results.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE runs (run TEXT, started DATETIME, score NUMERIC, note, _ TEXT);
+                 INSERT INTO runs VALUES ('007', '2024-01-05 10:00', 1, 12, 'r1');",
+            )
+            .unwrap();
+        let sqlite = |query: &str| SqliteOptions {
+            query: Some(query.to_string()),
+            ..SqliteOptions::default()
+        };
+
+        let records = read_sqlite_records(&path, &SqliteOptions::default()).unwrap();
+        // Declared types win over what the values look like
+        assert_eq!(
+            records.schema.unwrap().types,
+            vec![
+                ColumnType::String,
+                ColumnType::Timestamp,
+                ColumnType::Float,
+                ColumnType::Integer,
+                ColumnType::String
+            ]
+        );
+
+        let records = read_sqlite_records(
+            &path,
+            &sqlite("SELECT score * 2, score / 3.0, run || 'x' FROM runs"),
+        )
+        .unwrap();
+        assert_eq!(
+            records.schema.unwrap().types,
+            vec![ColumnType::Integer, ColumnType::Float, ColumnType::String]
+        );
+    }
+
+    #[test]
+    fn test_err_table_choice() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let path = tmpdir.path().join("results.db");
+        create_database(&path);
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("CREATE TABLE other (a TEXT);")
+            .unwrap();
+
+        let err = read_sqlite_records(&path, &SqliteOptions::default()).unwrap_err();
+        assert!(err.to_string().ends_with("evals, other"), "{err}");
+
+        let options = SqliteOptions {
+            table: Some("missing".to_string()),
+            ..SqliteOptions::default()
+        };
+        assert!(read_sqlite_records(&path, &options).is_err());
+    }
+
+    #[test]
+    fn test_parse_sqlite_file() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(tmpdir.path().join("r1")).unwrap();
+        let path = tmpdir.path().join("results.db");
+        create_database(&path);
+        let sqlite = SqliteOptions {
+            table: Some("evals".to_string()),
+            ..SqliteOptions::default()
+        };
+        let options = LoadOptions {
+            lenient: true,
+            ..LoadOptions::default()
+        };
+
+        let table = SqliteSource::new(&path, sqlite, options).read().unwrap();
+
+        assert_eq!(table.headers, vec!["model", "epoch", "loss"]);
+        assert_eq!(
+            table.schema.types,
+            vec![ColumnType::String, ColumnType::Integer, ColumnType::Float]
+        );
+        assert_eq!(table.rows, vec![vec!["unet", "12", "0.15"]]);
+        assert_eq!(table.image_dirs, vec![tmpdir.path().join("r1")]);
+        assert_eq!(table.issues.len(), 1);
+        assert_eq!(table.issues[0].line, 2);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use color_eyre::eyre;
+use serde::Deserialize;
+
+use crate::dataset::Value;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Integer,
+    Float,
+    Bool,
+    Timestamp,
+    // Anything else, treated as categories and compared naturally
+    String,
+}
+
+const TYPE_NAMES: &[(ColumnType, &str)] = &[
+    (ColumnType::Integer, "integer"),
+    (ColumnType::Float, "float"),
+    (ColumnType::Bool, "bool"),
+    (ColumnType::Timestamp, "timestamp"),
+    (ColumnType::String, "string"),
+];
+
+// Tried in order, so a column of whole numbers is integer rather than float
+pub(crate) const INFERRED_TYPES: [ColumnType; 4] = [
+    ColumnType::Integer,
+    ColumnType::Float,
+    ColumnType::Bool,
+    ColumnType::Timestamp,
+];
+
+// Column types by column name, overriding the inferred ones
+pub type TypeOverrides = BTreeMap<String, ColumnType>;
+
+// Python writes `True` and `False`
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.to_ascii_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+// ISO 8601 dates and date-times; those without a time zone are taken as UTC
+fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+        return Some(timestamp.to_utc());
+    }
+    // How Parquet prints its timestamps
+    if let Ok(timestamp) = DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f %:z") {
+        return Some(timestamp.to_utc());
+    }
+    [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .ok()
+            .map(|date| date.and_time(NaiveTime::MIN))
+    })
+    .map(|timestamp| timestamp.and_utc())
+}
+
+impl ColumnType {
+    pub fn name(&self) -> &'static str {
+        TYPE_NAMES
+            .iter()
+            .find(|(column_type, _)| column_type == self)
+            .map_or("", |(_, name)| name)
+    }
+
+    // Numbers are right-aligned in the Data tab
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, ColumnType::Integer | ColumnType::Float)
+    }
+
+    // Cells that don't fit the type stay text
+    pub fn parse<'a>(&self, text: &'a str) -> Value<'a> {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return Value::Empty;
+        }
+        let value = match self {
+            ColumnType::Integer => trimmed.parse().ok().map(Value::Integer),
+            // Rust also parses `nan` and `inf`, which are more likely words than numbers
+            ColumnType::Float => trimmed
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .map(Value::Float),
+            ColumnType::Bool => parse_bool(trimmed).map(Value::Bool),
+            ColumnType::Timestamp => parse_timestamp(trimmed).map(Value::Timestamp),
+            ColumnType::String => None,
+        };
+        value.unwrap_or(Value::Text(text))
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ColumnType {
+    type Err = eyre::Report;
+
+    fn from_str(name: &str) -> eyre::Result<Self> {
+        TYPE_NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(column_type, _)| *column_type)
+            .ok_or_else(|| {
+                let names: Vec<&str> = TYPE_NAMES.iter().map(|(_, name)| *name).collect();
+                eyre::eyre!(
+                    "Unknown column type \"{name}\", expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+// The type of every column, by position
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub types: Vec<ColumnType>,
+}
+
+impl Schema {
+    // A column gets the first type that every non-empty cell fits; empty columns are strings
+    pub fn infer(rows: &[Vec<String>], column_count: usize) -> Self {
+        let types = (0..column_count)
+            .map(|column| {
+                let mut cells = rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .filter(|cell| !cell.trim().is_empty())
+                    .peekable();
+                if cells.peek().is_none() {
+                    return ColumnType::String;
+                }
+                let mut candidates = INFERRED_TYPES.to_vec();
+                for cell in cells {
+                    candidates
+                        .retain(|column_type| !matches!(column_type.parse(cell), Value::Text(_)));
+                    if candidates.is_empty() {
+                        break;
+                    }
+                }
+                candidates.first().copied().unwrap_or(ColumnType::String)
+            })
+            .collect();
+        Schema { types }
+    }
+
+    // Names that aren't in `headers` are ignored
+    pub fn with_overrides(mut self, headers: &[String], overrides: &TypeOverrides) -> Self {
+        for (column, header) in headers.iter().enumerate() {
+            if let (Some(&column_type), Some(slot)) =
+                (overrides.get(header), self.types.get_mut(column))
+            {
+                *slot = column_type;
+            }
+        }
+        self
+    }
+
+    pub fn column_type(&self, column: usize) -> ColumnType {
+        self.types
+            .get(column)
+            .copied()
+            .unwrap_or(ColumnType::String)
+    }
+
+    pub fn value<'a>(&self, column: usize, text: &'a str) -> Value<'a> {
+        self.column_type(column).parse(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_types() {
+        let data: Vec<Vec<String>> = [
+            ["12", "0.5", "True", "2024-01-05", "unet", ""],
+            ["-3", "2", "false", "2024-01-05T10:30:00Z", "7", ""],
+            ["", "1e-3", "", "2024-01-06 08:00:00", "vit", ""],
+            ["", "", "", "", "", "nan"],
+        ]
+        .iter()
+        .map(|row| row.iter().map(|v| v.to_string()).collect())
+        .collect();
+
+        assert_eq!(
+            Schema::infer(&data, 6).types,
+            vec![
+                ColumnType::Integer,
+                ColumnType::Float,
+                ColumnType::Bool,
+                ColumnType::Timestamp,
+                ColumnType::String,
+                ColumnType::String,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overrides() {
+        let headers = vec!["epoch".to_string(), "run".to_string()];
+        let overrides = TypeOverrides::from([
+            ("run".to_string(), ColumnType::String),
+            ("missing".to_string(), ColumnType::Float),
+        ]);
+
+        let data = vec![vec!["1".to_string(), "2".to_string()]];
+        let schema = Schema::infer(&data, 2).with_overrides(&headers, &overrides);
+
+        assert_eq!(schema.types, vec![ColumnType::Integer, ColumnType::String]);
+        assert_eq!(schema.column_type(5), ColumnType::String);
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(ColumnType::Integer.parse(" 12 "), Value::Integer(12));
+        assert_eq!(ColumnType::Integer.parse("n/a"), Value::Text("n/a"));
+        assert_eq!(ColumnType::Float.parse(""), Value::Empty);
+        assert_eq!(ColumnType::Float.parse("1e-3"), Value::Float(0.001));
+        for word in ["nan", "inf", "-Infinity", "1e999"] {
+            assert_eq!(ColumnType::Float.parse(word), Value::Text(word));
+        }
+        assert_eq!(ColumnType::String.parse("12"), Value::Text("12"));
+
+        let timestamp = |text| match ColumnType::Timestamp.parse(text) {
+            Value::Timestamp(timestamp) => timestamp.timestamp(),
+            value => panic!("{value:?}"),
+        };
+        assert_eq!(timestamp("1970-01-02"), 86400);
+        assert_eq!(timestamp("1970-01-01T02:00:00+01:00"), 3600);
+        assert_eq!(timestamp("1970-01-01 01:00:00 +00:00"), 3600);
+        assert_eq!(timestamp("1970-01-01 00:01"), 60);
+    }
+
+    #[test]
+    fn test_type_names() {
+        for (column_type, name) in TYPE_NAMES {
+            assert_eq!(name.parse::<ColumnType>().unwrap(), *column_type);
+            assert_eq!(column_type.to_string(), *name);
+        }
+        assert!("number".parse::<ColumnType>().is_err());
+    }
+}
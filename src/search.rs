@@ -0,0 +1,174 @@
+use color_eyre::eyre;
+use regex::Regex;
+
+use crate::AdjustDirection;
+
+// A query over table cells.
+// `text` matches as a substring, case-insensitively unless it contains uppercase letters.
+// `~pattern` matches as a regular expression.
+// Either form can be scoped to a single column as `column:query`.
+#[derive(Clone, Debug)]
+pub struct Search {
+    pub query: String,
+    pub column: Option<usize>,
+    regex: Regex,
+}
+
+impl Search {
+    pub fn parse(query: &str, headers: &[String]) -> eyre::Result<Self> {
+        let (column, pattern) = match query.split_once(':') {
+            Some((name, rest)) => match headers.iter().position(|h| h == name) {
+                Some(column) => (Some(column), rest),
+                None => (None, query),
+            },
+            None => (None, query),
+        };
+        if pattern.is_empty() {
+            return Err(eyre::eyre!("Empty search"));
+        }
+        let regex = match pattern.strip_prefix('~') {
+            Some(pattern) => Regex::new(pattern)?,
+            None if pattern.chars().any(char::is_uppercase) => Regex::new(&regex::escape(pattern))?,
+            None => Regex::new(&format!("(?i){}", regex::escape(pattern)))?,
+        };
+        Ok(Self {
+            query: query.to_string(),
+            column,
+            regex,
+        })
+    }
+
+    pub fn matches_cell(&self, column: usize, cell: &str) -> bool {
+        self.column.is_none_or(|c| c == column) && self.regex.is_match(cell)
+    }
+
+    pub fn matches_row(&self, row: &[String]) -> bool {
+        row.iter()
+            .enumerate()
+            .any(|(column, cell)| self.matches_cell(column, cell))
+    }
+
+    // Position in `order` of the nearest matching row after (or before) `position`, wrapping around.
+    // The row at `position` itself is only considered last.
+    pub fn find(
+        &self,
+        rows: &[Vec<String>],
+        order: &[usize],
+        position: usize,
+        direction: AdjustDirection,
+    ) -> Option<usize> {
+        let len = order.len();
+        (1..=len)
+            .map(|distance| match direction {
+                AdjustDirection::Next => (position + distance) % len,
+                AdjustDirection::Previous => (position + len - distance) % len,
+            })
+            .find(|&candidate| self.matches_row(&rows[order[candidate]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers() -> Vec<String> {
+        ["sample", "note"].iter().map(|h| h.to_string()).collect()
+    }
+
+    fn rows() -> Vec<Vec<String>> {
+        [
+            ["S-001", "ok"],
+            ["S-002", "Blurry"],
+            ["S-010", "sample missing"],
+            ["S-011", "ok"],
+        ]
+        .iter()
+        .map(|row| row.iter().map(|v| v.to_string()).collect())
+        .collect()
+    }
+
+    fn search(query: &str) -> Search {
+        Search::parse(query, &headers()).unwrap()
+    }
+
+    #[test]
+    fn test_smart_case() {
+        assert!(search("blurry").matches_row(&rows()[1]));
+        assert!(search("Blurry").matches_row(&rows()[1]));
+        assert!(!search("BLURRY").matches_row(&rows()[1]));
+    }
+
+    #[test]
+    fn test_plain_text_is_not_a_regex() {
+        assert!(!search("S-0.1").matches_row(&rows()[0]));
+    }
+
+    #[test]
+    fn test_regex() {
+        let search = search("~^S-01\\d$");
+
+        assert!(!search.matches_row(&rows()[0]));
+        assert!(search.matches_row(&rows()[2]));
+    }
+
+    #[test]
+    fn test_column_scope() {
+        let scoped = search("note:sample");
+
+        assert_eq!(scoped.column, Some(1));
+        assert!(scoped.matches_cell(1, "sample missing"));
+        assert!(!scoped.matches_cell(0, "sample missing"));
+    }
+
+    #[test]
+    fn test_unknown_column_prefix_is_part_of_query() {
+        let search = search("http://x");
+
+        assert_eq!(search.column, None);
+        assert!(search.matches_cell(0, "see http://x"));
+    }
+
+    #[test]
+    fn test_find_wraps_around() {
+        let order = [0, 1, 2, 3];
+        let search = search("ok");
+
+        assert_eq!(
+            search.find(&rows(), &order, 0, AdjustDirection::Next),
+            Some(3)
+        );
+        assert_eq!(
+            search.find(&rows(), &order, 3, AdjustDirection::Next),
+            Some(0)
+        );
+        assert_eq!(
+            search.find(&rows(), &order, 0, AdjustDirection::Previous),
+            Some(3)
+        );
+        assert_eq!(
+            search.find(&rows(), &order, 2, AdjustDirection::Previous),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_find_respects_order() {
+        let order = [3, 2];
+
+        assert_eq!(
+            search("S-0").find(&rows(), &order, 0, AdjustDirection::Next),
+            Some(1)
+        );
+        assert_eq!(
+            search("Blurry").find(&rows(), &order, 0, AdjustDirection::Next),
+            None
+        );
+    }
+
+    #[test]
+    fn test_err_invalid_queries() {
+        assert!(Search::parse("", &headers()).is_err());
+        assert!(Search::parse("note:", &headers()).is_err());
+        assert!(Search::parse("~(", &headers()).is_err());
+    }
+}
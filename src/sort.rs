@@ -0,0 +1,256 @@
+use std::cmp::Ordering;
+
+use crate::dataset::Value;
+use crate::schema::{ColumnType, Schema};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    pub fn reversed(&self) -> Self {
+        match self {
+            SortOrder::Ascending => SortOrder::Descending,
+            SortOrder::Descending => SortOrder::Ascending,
+        }
+    }
+
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            SortOrder::Ascending => "▲",
+            SortOrder::Descending => "▼",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SortKey {
+    pub column: usize,
+    pub order: SortOrder,
+}
+
+// Empty cells, and cells that don't fit their column's type, sort after the others whatever the order
+fn compare_values(a: &Value, b: &Value, order: SortOrder) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Empty => 2,
+        Value::Text(_) => 1,
+        _ => 0,
+    };
+    rank(a).cmp(&rank(b)).then_with(|| {
+        let ordering = a.compare(b).unwrap_or(Ordering::Equal);
+        match (order, a) {
+            (SortOrder::Descending, Value::Empty | Value::Text(_)) => ordering,
+            (SortOrder::Descending, _) => ordering.reverse(),
+            (SortOrder::Ascending, _) => ordering,
+        }
+    })
+}
+
+// Compares runs of digits by value, so "img2" sorts before "img10"
+pub fn compare_natural(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let take_digits = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(c);
+                    }
+                    digits
+                };
+                let x_digits = take_digits(&mut a_chars);
+                let y_digits = take_digits(&mut b_chars);
+                let x_trimmed = x_digits.trim_start_matches('0');
+                let y_trimmed = y_digits.trim_start_matches('0');
+                let ordering = x_trimmed
+                    .len()
+                    .cmp(&y_trimmed.len())
+                    .then_with(|| x_trimmed.cmp(y_trimmed))
+                    .then_with(|| x_digits.len().cmp(&y_digits.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.cmp(&y);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+// Row indices in sorted order; ties keep their current relative order.
+// Typed columns compare by value, string columns naturally.
+pub fn sorted_permutation(rows: &[Vec<String>], keys: &[SortKey], schema: &Schema) -> Vec<usize> {
+    fn cell(row: &[String], column: usize) -> &str {
+        row.get(column).map_or("", String::as_str)
+    }
+    // Cells are parsed once rather than on every comparison
+    let values: Vec<Vec<Value>> = rows
+        .iter()
+        .map(|row| {
+            keys.iter()
+                .map(|key| schema.value(key.column, cell(row, key.column)))
+                .collect()
+        })
+        .collect();
+
+    let mut permutation: Vec<usize> = (0..rows.len()).collect();
+    permutation.sort_by(|&a, &b| {
+        keys.iter()
+            .enumerate()
+            .map(|(index, key)| match schema.column_type(key.column) {
+                ColumnType::String => {
+                    let ordering =
+                        compare_natural(cell(&rows[a], key.column), cell(&rows[b], key.column));
+                    match key.order {
+                        SortOrder::Ascending => ordering,
+                        SortOrder::Descending => ordering.reverse(),
+                    }
+                }
+                _ => compare_values(&values[a][index], &values[b][index], key.order),
+            })
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+    permutation
+}
+
+// Reorders `items` so that the new item `i` is the old item `permutation[i]`
+pub fn apply_permutation<T>(items: &mut Vec<T>, permutation: &[usize]) {
+    let mut old: Vec<Option<T>> = std::mem::take(items).into_iter().map(Some).collect();
+    *items = permutation
+        .iter()
+        .filter_map(|&index| old.get_mut(index).and_then(Option::take))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(values: &[&[&str]]) -> Vec<Vec<String>> {
+        values
+            .iter()
+            .map(|row| row.iter().map(|v| v.to_string()).collect())
+            .collect()
+    }
+
+    fn ascending(column: usize) -> SortKey {
+        SortKey {
+            column,
+            order: SortOrder::Ascending,
+        }
+    }
+
+    fn descending(column: usize) -> SortKey {
+        SortKey {
+            column,
+            order: SortOrder::Descending,
+        }
+    }
+
+    fn sorted(data: &[Vec<String>], keys: &[SortKey]) -> Vec<usize> {
+        let columns = data.first().map_or(0, Vec::len);
+        sorted_permutation(data, keys, &Schema::infer(data, columns))
+    }
+
+    #[test]
+    fn test_natural_comparison() {
+        assert_eq!(compare_natural("img2", "img10"), Ordering::Less);
+        assert_eq!(compare_natural("img10", "img2"), Ordering::Greater);
+        assert_eq!(compare_natural("a", "b"), Ordering::Less);
+        assert_eq!(compare_natural("img", "img1"), Ordering::Less);
+        assert_eq!(compare_natural("img01", "img1"), Ordering::Greater);
+        assert_eq!(compare_natural("run7b", "run7b"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_sort_numeric_ascending_and_descending() {
+        let data = rows(&[&["10"], &["9"], &["0.5"], &["100"]]);
+
+        assert_eq!(sorted(&data, &[ascending(0)]), vec![2, 1, 0, 3]);
+        assert_eq!(sorted(&data, &[descending(0)]), vec![3, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_sort_numeric_empty_cells_last() {
+        let data = rows(&[&[""], &["2"], &["1"]]);
+
+        assert_eq!(sorted(&data, &[ascending(0)]), vec![2, 1, 0]);
+        assert_eq!(sorted(&data, &[descending(0)]), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_sort_typed_columns() {
+        let data = rows(&[
+            &["2024-03-01", "7"],
+            &["2023-12-31", "n/a"],
+            &["2024-01-15", ""],
+            &["", "10"],
+        ]);
+        let schema = Schema {
+            types: vec![ColumnType::Timestamp, ColumnType::Integer],
+        };
+
+        assert_eq!(Schema::infer(&data, 2).types[0], ColumnType::Timestamp);
+        assert_eq!(sorted(&data, &[ascending(0)]), vec![1, 2, 0, 3]);
+        assert_eq!(sorted(&data, &[descending(0)]), vec![0, 2, 1, 3]);
+        assert_eq!(
+            sorted_permutation(&data, &[ascending(1)], &schema),
+            vec![0, 3, 1, 2]
+        );
+        assert_eq!(
+            sorted_permutation(&data, &[descending(1)], &schema),
+            vec![3, 0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_sort_text_naturally() {
+        let data = rows(&[&["run10"], &["run2"], &["run1"]]);
+
+        assert_eq!(sorted(&data, &[ascending(0)]), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_multi_key_sort() {
+        let data = rows(&[
+            &["unet", "0.3"],
+            &["resnet", "0.1"],
+            &["unet", "0.2"],
+            &["resnet", "0.4"],
+        ]);
+
+        assert_eq!(
+            sorted(&data, &[ascending(0), descending(1)]),
+            vec![3, 1, 0, 2]
+        );
+    }
+
+    #[test]
+    fn test_sort_is_stable() {
+        let data = rows(&[&["b", "1"], &["a", "2"], &["b", "3"], &["a", "4"]]);
+
+        assert_eq!(sorted(&data, &[ascending(0)]), vec![1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn test_apply_permutation() {
+        let mut items = vec!["a", "b", "c"];
+        apply_permutation(&mut items, &[2, 0, 1]);
+
+        assert_eq!(items, vec!["c", "a", "b"]);
+    }
+}
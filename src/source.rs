@@ -0,0 +1,302 @@
+use std::borrow::Cow;
+use std::io::Read;
+use std::{fs, path};
+
+use color_eyre::Result;
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::dataset::{DataSource, Dataset, LoadOptions, Table};
+use crate::parse_csv::{CsvOptions, read_csv_table};
+use crate::parse_jsonl::read_jsonl_table;
+use crate::parse_parquet::{PARQUET_MAGIC, read_parquet_table};
+use crate::parse_sqlite::{SqliteOptions, is_sqlite, read_sqlite_table};
+
+// Read from standard input instead of a file
+pub const STDIN_PATH: &str = "-";
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+pub(crate) fn is_stdin(filepath: &path::Path) -> bool {
+    filepath == path::Path::new(STDIN_PATH)
+}
+
+// Compressed input is recognised by its magic bytes, so that it works for stdin too
+pub(crate) fn read_input(filepath: &path::Path) -> Result<Vec<u8>> {
+    let bytes = if is_stdin(filepath) {
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .wrap_err("Could not read standard input")?;
+        bytes
+    } else {
+        fs::read(filepath).wrap_err_with(|| format!("Could not read {}", filepath.display()))?
+    };
+    let decompressed = if bytes.starts_with(GZIP_MAGIC) {
+        let mut decoded = Vec::new();
+        flate2::read::MultiGzDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decoded)
+            .map(|_| decoded)
+    } else if bytes.starts_with(ZSTD_MAGIC) {
+        zstd::decode_all(bytes.as_slice())
+    } else {
+        return Ok(bytes);
+    };
+    decompressed.wrap_err_with(|| format!("Could not decompress {}", filepath.display()))
+}
+
+// A byte order mark overrides `encoding`
+pub(crate) fn decode_text<'a>(
+    filepath: &path::Path,
+    bytes: &'a [u8],
+    encoding: &'static encoding_rs::Encoding,
+) -> Result<Cow<'a, str>> {
+    let (text, encoding, had_errors) = encoding.decode(bytes);
+    if had_errors {
+        return Err(eyre::eyre!(
+            "{} is not valid {}",
+            filepath.display(),
+            encoding.name()
+        ));
+    }
+    Ok(text)
+}
+
+fn is_jsonl(bytes: &[u8]) -> bool {
+    bytes
+        .strip_prefix(UTF8_BOM)
+        .unwrap_or(bytes)
+        .trim_ascii_start()
+        .starts_with(b"{")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Jsonl,
+    Parquet,
+    Sqlite,
+}
+
+impl Format {
+    // Text formats may be compressed, e.g. `runs.jsonl.gz`
+    fn from_extension(filepath: &path::Path) -> Option<Self> {
+        let extension = |path: &path::Path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .map(str::to_ascii_lowercase)
+        };
+        let mut outer = extension(filepath)?;
+        let compressed = matches!(outer.as_str(), "gz" | "zst");
+        if compressed {
+            outer = extension(path::Path::new(filepath.file_stem()?))?;
+        }
+        match (outer.as_str(), compressed) {
+            ("csv" | "tsv", _) => Some(Format::Csv),
+            ("jsonl" | "ndjson", _) => Some(Format::Jsonl),
+            ("parquet", false) => Some(Format::Parquet),
+            ("db" | "sqlite" | "sqlite3", false) => Some(Format::Sqlite),
+            _ => None,
+        }
+    }
+
+    fn sniff(bytes: &[u8]) -> Self {
+        if bytes.starts_with(PARQUET_MAGIC) {
+            Format::Parquet
+        } else if is_jsonl(bytes) {
+            Format::Jsonl
+        } else {
+            Format::Csv
+        }
+    }
+}
+
+// A file in any format pixmux reads, with the options of each; only those of its own format are used
+#[derive(Clone, Debug, Default)]
+pub struct FileSource {
+    pub path: path::PathBuf,
+    pub csv: CsvOptions,
+    pub sqlite: SqliteOptions,
+    pub options: LoadOptions,
+}
+
+impl FileSource {
+    pub fn new(path: impl Into<path::PathBuf>, options: LoadOptions) -> Self {
+        FileSource {
+            path: path.into(),
+            options,
+            ..FileSource::default()
+        }
+    }
+
+    // The format is taken from the extension; stdin and other files are recognised by their content:
+    // SQLite and Parquet by their magic bytes, JSON Lines by their first `{` and anything else as CSV
+    pub fn read(&self) -> Result<Table> {
+        // Content read to sniff the format is kept for reading it
+        let (format, sniffed) = match Format::from_extension(&self.path) {
+            Some(format) => (format, None),
+            None if is_sqlite(&self.path) => (Format::Sqlite, None),
+            None => {
+                let bytes = read_input(&self.path)?;
+                (Format::sniff(&bytes), Some(bytes))
+            }
+        };
+        if format != Format::Sqlite && (self.sqlite.table.is_some() || self.sqlite.query.is_some())
+        {
+            return Err(eyre::eyre!(
+                "{} is not a SQLite database, so it has no tables to query",
+                self.path.display()
+            ));
+        }
+
+        let bytes = || sniffed.map_or_else(|| read_input(&self.path), Ok);
+        match format {
+            Format::Sqlite => read_sqlite_table(&self.path, &self.sqlite, &self.options),
+            Format::Parquet => read_parquet_table(&self.path, bytes()?, &self.options),
+            Format::Jsonl => read_jsonl_table(&self.path, &bytes()?, &self.options),
+            Format::Csv => read_csv_table(&self.path, &bytes()?, &self.csv, &self.options),
+        }
+    }
+}
+
+impl DataSource for FileSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&mut self) -> Result<Box<dyn Dataset>> {
+        Ok(Box::new(self.read()?))
+    }
+
+    fn can_reload(&self) -> bool {
+        !is_stdin(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::Value;
+    use crate::schema::{ColumnType, TypeOverrides};
+
+    #[test]
+    fn test_file_source_reload() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("r1")).unwrap();
+        fs::create_dir(root.path().join("r2")).unwrap();
+        let csv = root.path().join("index.csv");
+        fs::write(&csv, "model,_\nunet,r1\nvit,nope\n").unwrap();
+        let options = LoadOptions {
+            lenient: true,
+            ..LoadOptions::default()
+        };
+        let mut source = FileSource::new(&csv, options);
+
+        let dataset = source.load().unwrap();
+        assert_eq!(dataset.headers(), ["model"]);
+        assert_eq!(dataset.row_count(), 1);
+        assert_eq!(dataset.issues().len(), 1);
+        assert_eq!(dataset.image_dir(0), root.path().join("r1"));
+
+        fs::write(&csv, "model,_\nunet,r1\nvit,r2\n").unwrap();
+        assert!(source.can_reload());
+        let dataset = source.load().unwrap();
+        assert_eq!(dataset.row_count(), 2);
+        assert_eq!(dataset.cell(1, 0), "vit");
+        assert_eq!(dataset.line(1), 3);
+        assert!(dataset.issues().is_empty());
+
+        source.options.lenient = false;
+        fs::write(&csv, "model,_\nvit,nope\n").unwrap();
+        assert!(source.load().is_err());
+    }
+
+    #[test]
+    fn test_file_source_type_overrides() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("r1")).unwrap();
+        let csv = root.path().join("index.csv");
+        fs::write(&csv, "run,_\n007,r1\n").unwrap();
+
+        for lenient in [false, true] {
+            let options = LoadOptions {
+                column_types: TypeOverrides::from([("run".to_string(), ColumnType::String)]),
+                lenient,
+                ..LoadOptions::default()
+            };
+            let dataset = FileSource::new(&csv, options).load().unwrap();
+            assert_eq!(dataset.schema().types, vec![ColumnType::String]);
+            assert_eq!(dataset.value(0, 0), Value::Text("007"));
+        }
+    }
+
+    #[test]
+    fn test_err_table_on_csv() {
+        let root = tempfile::tempdir().unwrap();
+        let csv = root.path().join("index.csv");
+        fs::write(&csv, "a,_\n1,x\n").unwrap();
+        let source = FileSource {
+            sqlite: SqliteOptions {
+                table: Some("evals".to_string()),
+                query: None,
+            },
+            ..FileSource::new(&csv, LoadOptions::default())
+        };
+
+        assert!(source.read().is_err());
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        let test_cases = [
+            ("runs.csv", Some(Format::Csv)),
+            ("runs.TSV", Some(Format::Csv)),
+            ("runs.jsonl.gz", Some(Format::Jsonl)),
+            ("runs.ndjson", Some(Format::Jsonl)),
+            ("runs.parquet", Some(Format::Parquet)),
+            ("runs.db", Some(Format::Sqlite)),
+            ("runs.sqlite.zst", None),
+            ("runs.txt", None),
+            ("runs", None),
+            (STDIN_PATH, None),
+        ];
+        for (path, expected) in test_cases {
+            assert_eq!(
+                Format::from_extension(path::Path::new(path)),
+                expected,
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extension_wins_over_content() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("r1")).unwrap();
+        let csv = root.path().join("index.csv");
+        fs::write(&csv, "{config},_\n{},r1\n").unwrap();
+        let jsonl = root.path().join("index.jsonl");
+        fs::write(&jsonl, "{\"model\": \"unet\", \"_\": \"r1\"}\n").unwrap();
+        let unknown = root.path().join("index.txt");
+        fs::write(&unknown, "{\"model\": \"unet\", \"_\": \"r1\"}\n").unwrap();
+
+        let dataset = FileSource::new(&csv, LoadOptions::default())
+            .read()
+            .unwrap();
+        assert_eq!(dataset.headers, ["{config}"]);
+        let dataset = FileSource::new(&jsonl, LoadOptions::default())
+            .read()
+            .unwrap();
+        assert_eq!(dataset.headers, ["model"]);
+        let dataset = FileSource::new(&unknown, LoadOptions::default())
+            .read()
+            .unwrap();
+        assert_eq!(dataset.headers, ["model"]);
+    }
+
+    #[test]
+    fn test_stdin_cannot_reload() {
+        let source = FileSource::new(STDIN_PATH, LoadOptions::default());
+        assert!(!source.can_reload());
+    }
+}
@@ -1,26 +1,29 @@
-#[derive(Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
 pub enum Tab {
     #[default]
     Data,
     Image,
+    Coverage,
 }
 
 impl Tab {
     pub fn cycle(&self) -> Self {
         match self {
             Tab::Data => Tab::Image,
-            Tab::Image => Tab::Data,
+            Tab::Image => Tab::Coverage,
+            Tab::Coverage => Tab::Data,
         }
     }
 
     pub fn titles() -> Vec<&'static str> {
-        vec!["Data", "Image"]
+        vec!["Data", "Image", "Coverage"]
     }
 
     pub fn to_index(&self) -> usize {
         match self {
             Tab::Data => 0,
             Tab::Image => 1,
+            Tab::Coverage => 2,
         }
     }
 }
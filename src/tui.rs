@@ -0,0 +1,37 @@
+use color_eyre::Result;
+use ratatui_image::picker::ProtocolType;
+
+use crate::app::App;
+use crate::config::Config;
+use crate::dataset::DataSource;
+
+#[derive(Clone, Debug)]
+pub struct TuiOptions {
+    // Queried from the terminal when not set
+    pub protocol: Option<ProtocolType>,
+    // Terminal cell size in pixels, queried from the terminal when not set
+    pub font_size: Option<(u16, u16)>,
+    // Rows before and after the current one whose images are decoded ahead of time
+    pub prefetch_depth: usize,
+    // Memory budget for decoded images, in bytes
+    pub cache_memory: usize,
+}
+
+impl Default for TuiOptions {
+    fn default() -> Self {
+        Self {
+            protocol: None,
+            font_size: None,
+            prefetch_depth: 2,
+            cache_memory: 256 * 1024 * 1024,
+        }
+    }
+}
+
+// Shows the source until the user quits; the terminal is restored before any error is returned
+pub fn run(source: Box<dyn DataSource>, config: Config, options: &TuiOptions) -> Result<()> {
+    let terminal = ratatui::init();
+    let result = App::new(source, config, options).and_then(|app| app.run(terminal));
+    ratatui::restore();
+    result
+}
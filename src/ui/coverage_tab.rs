@@ -0,0 +1,180 @@
+use ratatui::Frame;
+use ratatui::layout::{Alignment, Constraint, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+
+use crate::app::App;
+use crate::coverage::ImageStatus;
+
+const IMAGES_HEADER: &str = "imgs";
+// Widest key column value shown before truncating
+const MAX_KEY_WIDTH: u16 = 24;
+
+pub struct CoverageTabUI;
+
+impl CoverageTabUI {
+    fn gutter_width(app: &App) -> u16 {
+        app.table_rows.len().max(1).to_string().len() as u16
+    }
+
+    fn images_width(app: &App) -> u16 {
+        let expected = app.imagefile_basenames.len().to_string().len() as u16;
+        (2 * expected + 1).max(IMAGES_HEADER.len() as u16)
+    }
+
+    fn key_width(app: &App) -> u16 {
+        app.key_column
+            .and_then(|col| app.column_widths.get(col))
+            .map_or(0, |&width| width.min(MAX_KEY_WIDTH))
+    }
+
+    fn status_cell(status: ImageStatus) -> Cell<'static> {
+        match status {
+            ImageStatus::Present => Cell::from("●").green(),
+            ImageStatus::Missing => Cell::from("·").red(),
+            ImageStatus::Unreadable => Cell::from("!").magenta(),
+        }
+    }
+
+    // Only the rows at the positions in `visible_rows` and the images in `visible_images` are turned into widgets
+    pub fn create_widget<'a>(
+        app: &'a App,
+        visible_rows: std::ops::Range<usize>,
+        visible_images: std::ops::Range<usize>,
+    ) -> Table<'a> {
+        let coverage = &app.coverage;
+        let expected = app.imagefile_basenames.len();
+        let mut constraints = vec![
+            Constraint::Length(Self::gutter_width(app)),
+            Constraint::Length(Self::key_width(app)),
+            Constraint::Length(Self::images_width(app)),
+        ];
+        constraints.extend(visible_images.clone().map(|_| Constraint::Length(1)));
+
+        let rows = app.visible_rows[visible_rows.clone()]
+            .iter()
+            .zip(visible_rows)
+            .map(|(&row_index, position)| {
+                let id = app.row_ids[row_index];
+                let key = app
+                    .key_column
+                    .map_or("", |col| app.table_rows[row_index][col].as_str());
+                let present = coverage.present(id);
+                let count = Cell::from(format!("{present}/{expected}"));
+                let count = if present == expected {
+                    count.dark_gray()
+                } else if present == 0 {
+                    count.red()
+                } else {
+                    count.yellow()
+                };
+                let cells = [
+                    Cell::from((position + 1).to_string()).dark_gray(),
+                    Cell::from(key),
+                    count,
+                ]
+                .into_iter()
+                .chain(
+                    visible_images
+                        .clone()
+                        .map(|image| Self::status_cell(coverage.status(id, image))),
+                )
+                .collect::<Vec<_>>();
+                Row::new(cells)
+            })
+            .collect::<Vec<_>>();
+
+        let key_header = app
+            .key_column
+            .map_or("", |col| app.col_headers[col].as_str());
+        let images_header = match app.coverage_sort {
+            Some(order) => format!("{IMAGES_HEADER}{}", order.arrow()),
+            None => IMAGES_HEADER.to_string(),
+        };
+        // A marker over the focused image, with the images numbered in tens to help counting
+        let header_cells = [
+            Cell::from("#"),
+            Cell::from(key_header),
+            Cell::from(images_header),
+        ]
+        .into_iter()
+        .chain(visible_images.map(|image| {
+            if image == app.coverage_col_index {
+                Cell::from("▼").yellow()
+            } else if (image + 1) % 10 == 0 {
+                Cell::from(((image + 1) / 10 % 10).to_string())
+            } else {
+                Cell::from("")
+            }
+        }))
+        .collect::<Vec<_>>();
+
+        let title = app
+            .imagefile_basenames
+            .get(app.coverage_col_index)
+            .map(|basename| format!(" {basename} ({}/{expected}) ", app.coverage_col_index + 1))
+            .unwrap_or_default();
+        let title = if app.is_scanning_coverage() {
+            format!("{title}─ scanning… ")
+        } else {
+            title
+        };
+        Table::new(rows, constraints)
+            .header(Row::new(header_cells).bold().gray().underlined())
+            .row_highlight_style(Style::new().bold())
+            .cell_highlight_style(Style::new().reversed())
+            .block(Block::default().borders(Borders::ALL).title(title))
+    }
+
+    pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
+        if app.imagefile_basenames.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No images found in any row directory")
+                    .alignment(Alignment::Center)
+                    .block(Block::default().borders(Borders::ALL)),
+                area,
+            );
+            return;
+        }
+        app.start_coverage_scan();
+
+        // Borders take two lines and the header one
+        app.table_page_size = area.height.saturating_sub(3).max(1) as usize;
+        let len = app.visible_rows.len();
+        let selected = app.visible_position();
+        app.table_offset =
+            crate::scroll_offset(app.table_offset, selected, app.table_page_size, len);
+        let visible_rows = app.table_offset..(app.table_offset + app.table_page_size).min(len);
+
+        // Every image column is one cell wide plus the spacing
+        let images = app.imagefile_basenames.len();
+        let available = area
+            .width
+            .saturating_sub(2)
+            .saturating_sub(Self::gutter_width(app) + 1)
+            .saturating_sub(Self::key_width(app) + 1)
+            .saturating_sub(Self::images_width(app) + 1);
+        let viewport = (available as usize).div_ceil(2).max(1);
+        app.coverage_col_offset = crate::scroll_offset(
+            app.coverage_col_offset,
+            app.coverage_col_index,
+            viewport,
+            images,
+        );
+        let visible_images =
+            app.coverage_col_offset..(app.coverage_col_offset + viewport).min(images);
+
+        let mut state = TableState::default()
+            .with_selected(
+                (selected < len)
+                    .then(|| selected.checked_sub(app.table_offset))
+                    .flatten(),
+            )
+            .with_selected_column(Some(3 + app.coverage_col_index - app.coverage_col_offset));
+        frame.render_stateful_widget(
+            Self::create_widget(app, visible_rows, visible_images),
+            area,
+            &mut state,
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Clear, Row, Table};
+
+use crate::app::App;
+
+const MAX_WIDTH: u16 = 72;
+
+pub struct HelpUI;
+
+impl HelpUI {
+    // Lists what `on_key_event` would do for each key on the focused tab
+    pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
+        let bindings = app.keymap.active_bindings(app.current_tab);
+        let rows: Vec<Row> = bindings
+            .iter()
+            .map(|(action, keys)| {
+                let keys = keys
+                    .iter()
+                    .map(|sequence| crate::keymap::format_sequence(sequence))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Row::new([
+                    Cell::from(keys).yellow(),
+                    Cell::from(action.description()),
+                    Cell::from(action.name()).dark_gray(),
+                ])
+            })
+            .collect();
+
+        let height = (rows.len() as u16).saturating_add(2).min(area.height);
+        let [popup] = Layout::horizontal([Constraint::Max(MAX_WIDTH)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [popup] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(popup);
+
+        let visible = popup.height.saturating_sub(2) as usize;
+        app.overlay_page_size = visible.max(1);
+        app.help_offset = app.help_offset.min(rows.len().saturating_sub(visible));
+        let tab = crate::Tab::titles()[app.current_tab.to_index()];
+        let table = Table::new(
+            rows.into_iter().skip(app.help_offset),
+            [
+                Constraint::Length(16),
+                Constraint::Fill(1),
+                Constraint::Length(18),
+            ],
+        )
+        .block(
+            Block::bordered()
+                .title(format!(" Keys: {tab} tab "))
+                .title_bottom(
+                    Line::from(format!(
+                        " {} to close ",
+                        app.overlay_close_keys().join(", ")
+                    ))
+                    .right_aligned()
+                    .dark_gray(),
+                ),
+        );
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(table, popup);
+    }
+}
@@ -1,47 +1,30 @@
-use std::ptr;
-
-use pixmux::Pane;
 use ratatui::Frame;
-use ratatui::layout::{Constraint, Layout, Rect};
-use ratatui::style::Color;
-use ratatui::widgets::Block;
-use ratatui_image::{StatefulImage, picker};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Stylize};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui_image::Image;
 
+use crate::Pane;
 use crate::app::App;
+use crate::image_cache::CacheEntry;
 
 pub struct ImageTabUI;
 
+struct LeafArea {
+    path: Vec<bool>,
+    image_id: usize,
+    area: Rect,
+}
+
 impl ImageTabUI {
-    fn render_pane(pane: &Pane, frame: &mut Frame, area: Rect, app: &App, picker: &picker::Picker) {
+    fn layout_panes(pane: &Pane, area: Rect, path: &mut Vec<bool>, leaves: &mut Vec<LeafArea>) {
         match pane {
-            Pane::Leaf { image_id } => {
-                let block = Block::bordered();
-
-                let current_pane = app.pane_tree.get_node_at(&app.current_pane_path).unwrap();
-                if ptr::eq(current_pane, pane) {
-                    frame.render_widget(block.clone().style(Color::LightYellow), area);
-                } else {
-                    frame.render_widget(block.clone(), area);
-                }
-
-                let imagedir = app.imagedir_paths.get(app.current_datarow_index).unwrap();
-                let imagefile_basename = app.imagefile_basenames.get(*image_id).unwrap();
-                let imagefile = imagedir.join(imagefile_basename);
-
-                if imagefile.exists() {
-                    let image_source = image::ImageReader::open(imagefile)
-                        .unwrap()
-                        .decode()
-                        .unwrap();
-                    let mut image = picker.new_resize_protocol(image_source);
-
-                    frame.render_stateful_widget(
-                        StatefulImage::default(),
-                        block.inner(area),
-                        &mut image,
-                    );
-                }
-            }
+            Pane::Leaf { image_id } => leaves.push(LeafArea {
+                path: path.clone(),
+                image_id: *image_id,
+                area,
+            }),
             Pane::Split {
                 direction,
                 pct,
@@ -58,14 +41,104 @@ impl ImageTabUI {
                     .direction(*direction)
                     .constraints(constraints)
                     .split(area);
-                Self::render_pane(first, frame, chunks[0], app, picker);
-                Self::render_pane(second, frame, chunks[1], app, picker);
+
+                path.push(true);
+                Self::layout_panes(first, chunks[0], path, leaves);
+                path.pop();
+
+                path.push(false);
+                Self::layout_panes(second, chunks[1], path, leaves);
+                path.pop();
             }
         }
     }
 
-    pub fn render(frame: &mut Frame, area: Rect, app: &App) {
-        let picker = picker::Picker::from_query_stdio().unwrap();
-        Self::render_pane(&app.pane_tree, frame, area, app, &picker);
+    fn render_leaf(leaf: &LeafArea, frame: &mut Frame, app: &mut App) {
+        let block = Block::bordered();
+        if leaf.path == app.current_pane_path {
+            frame.render_widget(block.clone().style(Color::LightYellow), leaf.area);
+        } else {
+            frame.render_widget(block.clone(), leaf.area);
+        }
+        let inner = block.inner(leaf.area);
+
+        let Some(&row) = app.visible_rows.get(app.visible_position()) else {
+            Self::render_placeholder(frame, inner, "No rows to show".dark_gray());
+            return;
+        };
+        let Some(imagedir) = app.imagedir_paths.get(row) else {
+            Self::render_placeholder(frame, inner, "No image directory for this row".red());
+            return;
+        };
+        let Some(imagefile_basename) = app.imagefile_basenames.get(leaf.image_id) else {
+            let message = if app.imagefile_basenames.is_empty() {
+                "No images found in any row directory".to_string()
+            } else {
+                format!("No image #{}", leaf.image_id + 1)
+            };
+            Self::render_placeholder(frame, inner, message.red());
+            return;
+        };
+        let imagefile = imagedir.join(imagefile_basename);
+
+        if !app.has_image(row, leaf.image_id) {
+            let text = Text::from(vec![
+                Line::from("Missing in this row").yellow(),
+                Line::from(imagefile.display().to_string()).dark_gray(),
+            ]);
+            Self::render_placeholder(frame, inner, text);
+        } else {
+            match app.image_cache.get(&imagefile, inner) {
+                CacheEntry::Ready(protocol) => frame.render_widget(Image::new(protocol), inner),
+                CacheEntry::Loading => {
+                    Self::render_placeholder(frame, inner, "loading…".dark_gray())
+                }
+                CacheEntry::Failed(message) => {
+                    Self::render_placeholder(frame, inner, message.as_str().red())
+                }
+            }
+        }
+    }
+
+    fn render_placeholder<'a>(frame: &mut Frame, area: Rect, text: impl Into<Text<'a>>) {
+        frame.render_widget(
+            Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true }),
+            area,
+        );
+    }
+
+    fn prefetch_neighbours(leaves: &[LeafArea], app: &mut App) {
+        let positions = crate::neighbour_indices(
+            app.visible_position(),
+            app.visible_rows.len(),
+            app.prefetch_depth,
+        );
+        let mut requests = Vec::new();
+        for row in positions
+            .into_iter()
+            .map(|position| app.visible_rows[position])
+        {
+            for leaf in leaves {
+                if let (Some(basename), true) = (
+                    app.imagefile_basenames.get(leaf.image_id),
+                    app.has_image(row, leaf.image_id),
+                ) {
+                    let imagefile = app.imagedir_paths[row].join(basename);
+                    requests.push((imagefile, Block::bordered().inner(leaf.area)));
+                }
+            }
+        }
+        app.image_cache.prefetch(requests);
+    }
+
+    pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
+        let mut leaves = Vec::new();
+        Self::layout_panes(&app.pane_tree, area, &mut vec![], &mut leaves);
+        for leaf in &leaves {
+            Self::render_leaf(leaf, frame, app);
+        }
+        Self::prefetch_neighbours(&leaves, app);
     }
 }
@@ -0,0 +1,54 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Paragraph};
+
+use crate::app::App;
+
+const MAX_WIDTH: u16 = 100;
+
+pub struct LoadReportUI;
+
+impl LoadReportUI {
+    pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
+        let lines: Vec<Line> = app
+            .load_issues
+            .iter()
+            .map(|issue| Line::from(issue.to_string()))
+            .collect();
+
+        let height = (lines.len() as u16).saturating_add(2).min(area.height);
+        let [popup] = Layout::horizontal([Constraint::Max(MAX_WIDTH)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [popup] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(popup);
+
+        let visible = popup.height.saturating_sub(2) as usize;
+        app.overlay_page_size = visible.max(1);
+        app.load_report_offset = app
+            .load_report_offset
+            .min(lines.len().saturating_sub(visible));
+        let count = lines.len();
+        let rows = if count == 1 { "row" } else { "rows" };
+        let report = Paragraph::new(lines)
+            .scroll((app.load_report_offset as u16, 0))
+            .block(
+                Block::bordered()
+                    .title(format!(" Skipped {count} {rows} ").yellow())
+                    .title_bottom(
+                        Line::from(format!(
+                            " {} to close ",
+                            app.overlay_close_keys().join(", ")
+                        ))
+                        .right_aligned()
+                        .dark_gray(),
+                    ),
+            );
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(report, popup);
+    }
+}
@@ -1,7 +1,15 @@
+mod coverage_tab;
+mod help;
 mod image_tab;
+mod load_report;
 mod navbar;
+mod status_line;
 mod table_tab;
 
+pub use coverage_tab::CoverageTabUI;
+pub use help::HelpUI;
 pub use image_tab::ImageTabUI;
+pub use load_report::LoadReportUI;
 pub use navbar::NavBarUI;
+pub use status_line::StatusLineUI;
 pub use table_tab::TableTabUI;
@@ -1,14 +1,21 @@
-use ratatui::style::{Color, Style};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
 use ratatui::widgets::{Block, Tabs};
 
-use crate::App;
+use crate::app::App;
 
 pub struct NavBarUI;
 
 impl NavBarUI {
     pub fn render(frame: &mut ratatui::Frame, area: ratatui::layout::Rect, app: &App) {
-        let tabs = Tabs::new(pixmux::Tab::titles())
-            .block(Block::bordered())
+        let tabs = Tabs::new(crate::Tab::titles())
+            .block(
+                Block::bordered().title(
+                    Line::from(format!("{:?}", app.picker.protocol_type()))
+                        .right_aligned()
+                        .dark_gray(),
+                ),
+            )
             .select(app.current_tab.to_index())
             .highlight_style(Style::default().fg(Color::Yellow));
         frame.render_widget(tabs, area);
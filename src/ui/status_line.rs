@@ -0,0 +1,119 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span};
+
+use crate::app::App;
+use crate::app::prompt::Prompt;
+
+pub struct StatusLineUI;
+
+impl StatusLineUI {
+    fn render_prompt(frame: &mut Frame, area: Rect, prompt: &Prompt) {
+        let mut spans = vec![
+            Span::from(prompt.kind.label()).bold(),
+            Span::from(prompt.input.as_str()),
+        ];
+        if let Some(error) = &prompt.error {
+            spans.push(Span::from(format!("  {error}")).red());
+        }
+        if let Some(completion) = &prompt.completion {
+            for (index, candidate) in completion.candidates.iter().enumerate() {
+                let span = Span::from(format!("  {candidate}"));
+                spans.push(if index == completion.index {
+                    span.yellow()
+                } else {
+                    span.dark_gray()
+                });
+            }
+        }
+        frame.render_widget(Line::from(spans), area);
+        let cursor_x = area.x + (prompt.kind.label().chars().count() + prompt.cursor) as u16;
+        frame.set_cursor_position(Position::new(
+            cursor_x.min(area.right().saturating_sub(1)),
+            area.y,
+        ));
+    }
+
+    // Where the selection is: row position, key column value and focused image
+    fn position_spans(app: &App) -> Vec<Span<'_>> {
+        let separator = || Span::from(" │ ").dark_gray();
+        let total = app.table_rows.len();
+        let len = app.visible_rows.len();
+        let mut spans = vec![if len == 0 {
+            Span::from("no rows")
+        } else {
+            Span::from(format!("row {}/{len}", app.visible_position() + 1))
+        }];
+        if len != total {
+            spans.push(Span::from(format!(" ({total} total)")).dark_gray());
+        }
+        let key = app.key_column.and_then(|column| {
+            let value = app.table_rows.get(app.current_datarow_index)?.get(column)?;
+            Some((&app.col_headers[column], value))
+        });
+        if let (Some((name, value)), true) = (key, len > 0) {
+            spans.push(separator());
+            spans.push(Span::from(format!("{name}=")).dark_gray());
+            spans.push(Span::from(value.as_str()));
+        }
+        if let Some(basename) = app.focused_image() {
+            spans.push(separator());
+            spans.push(Span::from(basename).cyan());
+        }
+        spans
+    }
+
+    // What shapes the view: filter, sort and search
+    fn view_spans(app: &App) -> Vec<Span<'_>> {
+        let mut spans = Vec::new();
+        if app.filter.is_some() {
+            spans.push(Span::from(format!("filter: {}", app.filter_expression)).yellow());
+        }
+        if !app.sort_keys.is_empty() {
+            let keys = app
+                .sort_keys
+                .iter()
+                .map(|key| format!("{}{}", app.col_headers[key.column], key.order.arrow()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            spans.push(Span::from(format!("sort: {keys}")).yellow());
+        }
+        if let Some(order) = app.coverage_sort {
+            spans.push(Span::from(format!("sort: coverage{}", order.arrow())).yellow());
+        }
+        if let Some(search) = &app.search {
+            spans.push(Span::from(format!("/{}", search.query)).yellow());
+        }
+        let mut separated = Vec::new();
+        for (index, span) in spans.into_iter().enumerate() {
+            if index > 0 {
+                separated.push(Span::from(" │ ").dark_gray());
+            }
+            separated.push(span);
+        }
+        separated
+    }
+
+    pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+        if let Some(prompt) = &app.prompt {
+            Self::render_prompt(frame, area, prompt);
+            return;
+        }
+
+        let left = Line::from(Self::position_spans(app));
+        // A message takes the place of the view details until it expires
+        let right = match &app.message {
+            Some(message) if message.is_error => Line::from(message.text.as_str()).red(),
+            Some(message) => Line::from(message.text.as_str()).green(),
+            None => Line::from(Self::view_spans(app)),
+        };
+        // The right side may take up to half the line from the left when both don't fit
+        let free = area.width.saturating_sub(left.width() as u16 + 1);
+        let right_width = (right.width() as u16).min(free.max(area.width / 2));
+        let [left_area, right_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(right_width)]).areas(area);
+        frame.render_widget(left, left_area);
+        frame.render_widget(right.right_aligned(), right_area);
+    }
+}
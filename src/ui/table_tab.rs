@@ -1,45 +1,153 @@
-use ratatui::layout::Constraint;
-use ratatui::style::Stylize;
-use ratatui::widgets::{Block, Borders, Cell, Row, Table};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Row, Table, TableState};
 
-use crate::App;
+use crate::app::App;
+
+const IMAGES_HEADER: &str = "imgs";
 
 pub struct TableTabUI;
 
 impl TableTabUI {
-    pub fn create_widget(app: &App) -> Table<'static> {
-        let collen = app.col_headers.len();
-        let constraints = vec![Constraint::Length(20); collen];
+    fn gutter_width(app: &App) -> u16 {
+        app.table_rows.len().max(1).to_string().len() as u16
+    }
+
+    // Fits "available/expected" for the widest expected count, and the header
+    fn images_width(app: &App) -> u16 {
+        let expected = app.imagefile_basenames.len().to_string().len() as u16;
+        (2 * expected + 1).max(IMAGES_HEADER.len() as u16)
+    }
+
+    fn images_cell(app: &App, row: usize) -> Cell<'_> {
+        let expected = app.imagefile_basenames.len();
+        let available = app.available_images(row);
+        let cell = Cell::from(format!("{available}/{expected}"));
+        if available == expected {
+            cell.dark_gray()
+        } else if available == 0 {
+            cell.red()
+        } else {
+            cell.yellow()
+        }
+    }
+
+    // Numbers are right-aligned so that their digits line up
+    fn aligned<'a>(app: &App, col: usize, text: impl Into<Line<'a>>) -> Cell<'a> {
+        let line = text.into();
+        if app.schema.column_type(col).is_numeric() {
+            Cell::from(line.right_aligned())
+        } else {
+            Cell::from(line)
+        }
+    }
 
-        let rows = app
-            .table_rows
+    // Only the visible rows at the positions in `visible_rows` and the columns in `visible_cols` are turned into widgets
+    pub fn create_widget<'a>(
+        app: &'a App,
+        visible_rows: std::ops::Range<usize>,
+        visible_cols: &[usize],
+    ) -> Table<'a> {
+        let mut constraints = vec![
+            Constraint::Length(Self::gutter_width(app)),
+            Constraint::Length(Self::images_width(app)),
+        ];
+        constraints.extend(
+            visible_cols
+                .iter()
+                .map(|&col| Constraint::Length(app.column_widths[col])),
+        );
+
+        let rows = app.visible_rows[visible_rows.clone()]
             .iter()
-            .enumerate()
-            .map(|(index, row)| {
-                let row_cells = row
-                    .iter()
-                    .map(|cell| Cell::from(cell.clone()))
+            .zip(visible_rows)
+            .map(|(&row_index, position)| {
+                let row = &app.table_rows[row_index];
+                let gutter = Cell::from((position + 1).to_string()).dark_gray();
+                let row_cells = [gutter, Self::images_cell(app, row_index)]
+                    .into_iter()
+                    .chain(visible_cols.iter().map(|&col| {
+                        let cell = Self::aligned(app, col, row[col].as_str());
+                        match &app.search {
+                            Some(search) if search.matches_cell(col, &row[col]) => {
+                                cell.black().on_yellow()
+                            }
+                            _ => cell,
+                        }
+                    }))
                     .collect::<Vec<_>>();
-                let mut table_row = Row::new(row_cells);
-                if index == app.current_datarow_index {
-                    table_row = table_row.reversed();
-                }
-                table_row
+                Row::new(row_cells)
             })
             .collect::<Vec<_>>();
 
+        let header_cells = [Cell::from("#"), Cell::from(IMAGES_HEADER)]
+            .into_iter()
+            .chain(visible_cols.iter().map(|&col| {
+                let title = &app.col_headers[col];
+                let header = match app.sort_keys.iter().find(|key| key.column == col) {
+                    Some(key) => Self::aligned(app, col, format!("{title}{}", key.order.arrow())),
+                    None => Self::aligned(app, col, title.as_str()),
+                };
+                if col == app.current_col_index {
+                    header.yellow()
+                } else {
+                    header
+                }
+            }))
+            .collect::<Vec<_>>();
+
         Table::new(rows, constraints)
-            .header(
-                Row::new(
-                    app.col_headers
-                        .iter()
-                        .map(|h| Cell::from(h.clone()))
-                        .collect::<Vec<_>>(),
-                )
-                .bold()
-                .gray()
-                .underlined(),
-            )
+            .header(Row::new(header_cells).bold().gray().underlined())
+            .row_highlight_style(Style::new().reversed())
+            .cell_highlight_style(Style::new().yellow())
             .block(Block::default().borders(Borders::ALL))
     }
+
+    pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
+        // Borders take two lines and the header one
+        app.table_page_size = area.height.saturating_sub(3).max(1) as usize;
+        let len = app.visible_rows.len();
+        let selected = app.visible_position();
+        app.table_offset =
+            crate::scroll_offset(app.table_offset, selected, app.table_page_size, len);
+        let visible_rows = app.table_offset..(app.table_offset + app.table_page_size).min(len);
+
+        let available = area
+            .width
+            .saturating_sub(2)
+            .saturating_sub(Self::gutter_width(app) + 1)
+            .saturating_sub(Self::images_width(app) + 1);
+        app.col_offset = crate::columns::scroll_columns(
+            &app.column_widths,
+            app.frozen_col_count,
+            app.col_offset,
+            app.current_col_index,
+            available,
+        );
+        let visible_cols = crate::columns::visible_columns(
+            &app.column_widths,
+            app.frozen_col_count,
+            app.col_offset,
+            available,
+        );
+        let selected_col = visible_cols
+            .iter()
+            .position(|&col| col == app.current_col_index)
+            .map(|pos| pos + 2);
+
+        let mut state = TableState::default()
+            .with_selected(
+                (selected < len)
+                    .then(|| selected.checked_sub(app.table_offset))
+                    .flatten(),
+            )
+            .with_selected_column(selected_col);
+        frame.render_stateful_widget(
+            Self::create_widget(app, visible_rows, &visible_cols),
+            area,
+            &mut state,
+        );
+    }
 }